@@ -5,12 +5,29 @@ extern crate toml;
 // Inner Crates
 use crate::input;
 use mcpp_core;
+use mcpp_core::compile_task::{overlay::OverlaidPack, McVersion};
 use crate::init::Enviroment;
 
-pub fn build(env_toml:&str, main:&str, target:&str) {
+/// Build the datapack with given features enabled.
+/// 
+/// If the project supports other versions of Minecraft, the source is compiled for each of them,
+/// then differences are put onto overlays of one pack.
+pub fn build(env_toml:&str, main:&str, target:&str, features:&[String]) {
     let env:Enviroment = toml::from_str(&input::load_a_file_inside(env_toml)).unwrap();
-    mcpp_core::compile_a_file(main)
-        .unwrap()
-        .build_datapack(&env.project_name, target)
-        .unwrap();
+    let features = match env.resolve_features(features) {
+        Ok(o) => o,
+        Err(e) => {
+            println!("{}", e);
+            return
+        }
+    };
+    let mut builds:Vec<(McVersion, mcpp_core::compile_task::MCFunction)> = Vec::new();
+    for mc_version in std::iter::once(&env.mc_version).chain(&env.supported_versions) {
+        let compiled = mcpp_core::compile_a_file(main, mc_version, env.float_precision, &env.overflow, features.clone()).unwrap();
+        builds.push((McVersion::parse(mc_version).unwrap(), compiled));
+    }
+    match builds.as_slice() {
+        [(mc_version, compiled)] => compiled.build_datapack(&env.project_name, target, *mc_version).unwrap(),
+        _ => OverlaidPack::plan(&builds).unwrap().build(&env.project_name, target).unwrap()
+    }
 }
\ No newline at end of file
@@ -1,8 +1,29 @@
-use crate::{build, init};
+use crate::{build, get_chest_root, init};
+
+/// Split `--features a,b` off given arguments, then return the features and the other arguments.
+fn split_features<'a>(args:&[&'a str]) -> (Vec<String>, Vec<&'a str>) {
+    let mut features:Vec<String> = Vec::new();
+    let mut others:Vec<&str> = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match *arg {
+            "--features" => features.extend(
+                args.next()
+                    .unwrap_or(&"")
+                    .split([',', ' '])
+                    .filter(|f| !f.is_empty())
+                    .map(|f| f.to_string())
+            ),
+            _ => others.push(arg)
+        }
+    }
+    (features, others)
+}
 
 pub fn solve_args(args:Vec<&str>, current_path:&str) {
     match args[0] {
         "build" => {
+            let (features, args) = split_features(&args);
             let root = match args.get(2) {
                 Some(s) => s.clone(),
                 None => current_path
@@ -11,7 +32,8 @@ pub fn solve_args(args:Vec<&str>, current_path:&str) {
             build::build(
                 format!("{}/MCPP.toml", &chest_root).as_str(),
                 format!("{}/src/main.mcpp", &chest_root).as_str(),
-                format!("{}/target", &chest_root).as_str()
+                format!("{}/target", &chest_root).as_str(),
+                &features
             );
         },
         "init" => {
@@ -3,21 +3,60 @@ extern crate toml;
 
 use crate::input;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Enviroment {
     pub project_name : String,
     pub mc_version : String,
+    /// Other versions of Minecraft which the pack supports as well, by overlays of pack.mcmeta.
+    #[serde(default)]
+    pub supported_versions : Vec<String>,
     pub test_world : String,
+    /// Digits under the decimal point of float, which can be overwritten by fixed<N>.
+    #[serde(default = "default_float_precision")]
+    pub float_precision : u32,
+    /// How int and float arithmetic handles an overflow, one of checked, saturating and wrapping.
+    #[serde(default = "default_overflow")]
+    pub overflow : String,
+    /// Features which can be enabled by `mcpp build --features`, and features each of them enables as well.
+    #[serde(default)]
+    pub features : BTreeMap<String, Vec<String>>,
+}
+fn default_float_precision() -> u32 {
+    3
+}
+fn default_overflow() -> String {
+    "wrapping".to_string()
 }
 impl Enviroment {
     fn new() -> Enviroment {
         Enviroment {
             project_name: "Untitled".to_string(),
             mc_version: "1.20.1".to_string(),
-            test_world: "THE_PATH_OF_TEST_WORLD_HERE".to_string()
+            supported_versions: Vec::new(),
+            test_world: "THE_PATH_OF_TEST_WORLD_HERE".to_string(),
+            float_precision: default_float_precision(),
+            overflow: default_overflow(),
+            features: BTreeMap::new()
+        }
+    }
+    /// Solve features enabled by given ones, including ones which they enable.
+    /// 
+    /// Every feature must be declared in the features table.
+    pub fn resolve_features(&self, requested:&[String]) -> Result<HashSet<String>, String> {
+        let mut enabled:HashSet<String> = HashSet::new();
+        let mut pending = requested.to_vec();
+        while let Some(feature) = pending.pop() {
+            let Some(implied) = self.features.get(&feature) else {
+                return Err(format!("The feature {} is not declared in the features table of MCPP.toml.", feature))
+            };
+            if enabled.insert(feature) {
+                pending.extend(implied.iter().cloned());
+            }
         }
+        Ok(enabled)
     }
 }
 
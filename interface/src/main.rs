@@ -57,7 +57,7 @@ fn main() {
             get_current_path().as_str()
         ),
         None => {
-            println!("Hello, you can try these subcommands.\nbuild (path)\n   The subcommand for compile a file.\nhelp\n  You can receive more detailed information.")
+            println!("Hello, you can try these subcommands.\nbuild (path) (--features a,b)\n   The subcommand for compile a file.\nhelp\n  You can receive more detailed information.")
         }
     }
 }
\ No newline at end of file
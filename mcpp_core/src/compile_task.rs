@@ -1,25 +1,168 @@
 use core::fmt;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+use call_graph::CallGraph;
+use schedule::After;
 use evaluater::EvaluateError;
 pub use scoreboard::Scoreboard;
 pub use mcfunction::MCFunction;
+pub use structure::Structure;
+pub use enumeration::Enumeration;
+pub use version::McVersion;
+pub use scoreboard::Overflow;
+pub use event::Event;
 
 use crate::{Language, CURRENT_LANGUAGE};
 
 pub mod evaluater;
 pub mod scoreboard;
 pub mod mcfunction;
+pub mod structure;
+pub mod enumeration;
+pub mod dispatch;
+pub mod call_graph;
+pub mod version;
+pub mod std_lib;
+pub mod event;
+pub mod schedule;
+pub mod cfg;
+pub mod overlay;
+pub mod init;
+mod matching;
+mod print;
+mod type_check;
+#[cfg(test)]
+mod simulator;
 
 #[test]
 fn test() {
     let mut compiler = CompileTask::new();
     println!("\n{}", (compiler.compile("if (1 - 1) * 0 {a = (0.1 + 2) * 5}", "test").unwrap().inside));
 }
+#[test]
+fn overflow_test() {
+    let source = "a:int = 1;
+        #[overflow(saturating)] if a { a = a * 3 }
+        #[overflow(checked)] fn grow(n:int) -> int { return n + 1 }";
+    let mut compiler = CompileTask::new();
+    let compiled = compiler.compile(source, "test").unwrap();
+    let block = compiled.child_func.iter().find(|f| f.name != "grow").unwrap();
+    let grow = compiled.child_func.iter().find(|f| f.name == "grow").unwrap();
+    println!("{}\n\n{}", block.inside, grow.inside);
+    assert!(!compiled.inside.contains("#Calc.OVERFLOW"));
+    assert!(block.inside.contains("execute if score #Calc.OVERFLOW.flag MCPP.var matches -1 run scoreboard players set #Calc.TEMP MCPP.var -2147483648"));
+    assert!(grow.inside.contains("MC++ runtime error"));
+
+    // An overflow in a block stops the whole function, not only the block.
+    let mut compiler = CompileTask::new();
+    let compiled = compiler.compile(
+        "reached:int = 0;
+        #[overflow(checked)] fn grow(n:int) { if n { n = n * 2147483647 } reached = 1 }
+        fn overflows() { grow(2) }
+        fn fits() { grow(0) }",
+        "test"
+    ).unwrap();
+    let mut simulator = simulator::Simulator::new(&compiled);
+    simulator.call("test:overflows");
+    assert_eq!(simulator.score("#reached"), None);
+    simulator.call("test:fits");
+    assert_eq!(simulator.score("#reached"), Some(1));
+
+    let mut compiler = CompileTask::new();
+    assert!(matches!(
+        compiler.compile("#[overflow(never)] if 1 { a = 1 }", "test"),
+        Err(SentenceError::TypeErrors(e)) if e[0].contains("never")
+    ));
+    compiler.mc_version = McVersion::MACRO;
+    compiler.overflow = Overflow::Checked;
+    assert!(matches!(compiler.compile("a = 1", "test"), Err(SentenceError::CheckedOverflowNotSupported(_))));
+}
+#[test]
+fn macro_test() {
+    let source = "macro fn tp_to(x:int, y:int, z:int) { run!(\"tp @s $(x) $(y) $(z)\") }
+        fn main() { tp_to(1, 2 + 3, 4) }";
+    let mut compiler = CompileTask::new();
+    let compiled = compiler.compile(source, "test").unwrap();
+    let tp_to = compiled.child_func.iter().find(|f| f.name == "tp_to").unwrap();
+    let main = compiled.child_func.iter().find(|f| f.name == "main").unwrap();
+    println!("{}\n\n{}", tp_to.inside, main.inside);
+    assert!(tp_to.inside.contains("$tp @s $(x) $(y) $(z)"));
+    assert!(main.inside.contains("execute store result storage mcpp:macro tp_to.y int 1 run scoreboard players get #tp_to.y MCPP.var"));
+    assert!(main.inside.contains("function test:tp_to with storage mcpp:macro tp_to"));
+
+    let mut old_compiler = CompileTask::new();
+    old_compiler.mc_version = McVersion::parse("1.20.1").unwrap();
+    assert!(matches!(old_compiler.compile(source, "test"), Err(SentenceError::MacroNotSupported(_))));
+}
+#[test]
+fn structure_test() {
+    let mut compiler = CompileTask::new();
+    let compiled = compiler.compile(
+        "struct Vec3 { x: float, y: float, z: float }
+        fn scale(v:Vec3, n:float) -> Vec3 { v.x = v.x * n; v.y = v.y * n; v.z = v.z * n; return v }
+        fn main() { pos:Vec3; pos.x = 1.5; moved:Vec3 = scale(pos, 2); pos = moved }",
+        "test"
+    ).unwrap();
+    let main = compiled.child_func.iter().find(|f| f.name == "main").unwrap();
+    println!("\n{}", main.inside);
+    assert!(main.inside.contains("scoreboard players operation #pos.x MCPP.var = #Calc.TEMP MCPP.var"));
+    assert!(main.inside.contains("scoreboard players operation #scale.v.y MCPP.var = #pos.y MCPP.var"));
+    assert!(main.inside.contains("function test:scale"));
+    assert!(main.inside.contains("scoreboard players operation #moved.z MCPP.var = #TEMP.RETURN_VALUE.scale.z MCPP.var"));
+    assert!(main.inside.contains("scoreboard players operation #pos.x MCPP.var = #moved.x MCPP.var"));
+}
+
+#[test]
+fn comment_test() {
+    let source = "a:int = 1 // the first
+        # a comment of Minecraft
+        b:int = 2; /* a block comment; with { and \" */ c:int = /* inline */ 3;
+        /// Heal every players.
+        ///
+        /// It is called by a command.
+        fn heal() { run!(\"effect give @a instant_health\") }
+        /// Ignored, because it is not on a function.
+        d:int = 4";
+    let mut compiler = CompileTask::new();
+    let compiled = compiler.compile(source, "test").unwrap();
+    println!("{}", compiled.inside);
+    assert!(compiled.inside.contains("# a comment of Minecraft"));
+    assert!(compiled.inside.contains("scoreboard players operation #a MCPP.var = #Calc.TEMP MCPP.var"));
+    assert!(compiled.inside.contains("scoreboard players operation #b MCPP.var = #Calc.TEMP MCPP.var"));
+    assert!(compiled.inside.contains("scoreboard players operation #c MCPP.var = #Calc.TEMP MCPP.var"));
+    assert!(compiled.inside.contains("scoreboard players operation #d MCPP.var = #Calc.TEMP MCPP.var"));
+    assert!(!compiled.inside.contains("first"));
+    let heal = compiled.child_func.iter().find(|f| f.name == "heal").unwrap();
+    println!("{}", heal.inside);
+    assert!(heal.inside.starts_with("# Heal every players.\n#\n# It is called by a command.\n"));
+    assert_eq!(Sentence::split_lines("x = \"// not a comment\"; y = 1"), vec!["x = \"// not a comment\"", "y = 1"]);
+}
+#[test]
+fn naming_test() {
+    let source = "a:int = 1; b:int = 2;
+        if a { a = 2 }
+        fn count(n:int) { while(n) { n = n - 1; if n { run!(\"say odd\") } } }
+        { b = 3 }";
+    let build = || {
+        let compiled = CompileTask::new().compile(source, "test").unwrap();
+        (compiled.name.clone(), compiled.collect_files(McVersion::parse("1.21.4").unwrap()))
+    };
+    let (name, files) = build();
+    println!("{:?}", files.keys().filter(|f| !f.contains(std_lib::STD_FOLDER)).collect::<Vec<&String>>());
+    assert_eq!(name, ROOT_FUNCTION);
+    assert!(files.contains_key("data/test/function/if_2.mcfunction"));
+    assert!(files.contains_key("data/test/function/count_while_0.mcfunction"));
+    assert!(files.contains_key("data/test/function/count_while_0_if_1.mcfunction"));
+    assert!(files.contains_key("data/test/function/block_4.mcfunction"));
+    // Builds of the same source are the same byte for byte.
+    assert!(files == build().1);
+}
 
 #[derive(Clone)]
 /// The struct compiles a sentense, the code areas between { and } in MC++.
 /// 
+/// Features of the language are described in their modules, like [schedule] or [std_lib].
+/// 
 /// # Namespace
 /// This is corresponding to a name of objective in Minecraft.
 /// 
@@ -65,6 +208,9 @@ fn test() {
 /// 
 /// local functions will be released at the end of sentence.
 /// 
+/// Anonymous sentences are named from their parent and their position, like if_2 or count_while_0,
+/// and the top level is named mcpp_main, so the same source is always built into the same files.
+/// 
 /// # Scope
 /// Variables have true name used in Minecraft.
 /// 
@@ -81,12 +227,29 @@ fn test() {
 /// In that case, the true name will be #foo.bar.baz
 /// 
 /// And it meant \["foo", "bar"\] part.
+/// 
+/// # Structures and Enumerations
+/// Structures declared by `struct Name {...}` and enumerations declared by `enum Name {...}`
+/// are visible from the sentence which declared them and its slave compilers.
 pub struct CompileTask {
     pub inherited_variables : HashMap<String, Scoreboard>,
     pub local_variables : HashMap<String, Scoreboard>,
     pub inherited_functions : HashMap<String, MCFunction>,
     pub local_functions : HashMap<String, MCFunction>,
+    pub structures : HashMap<String, Structure>,
+    pub enumerations : HashMap<String, Enumeration>,
     pub scope : Vec<String>,
+    pub ret_container : Option<Scoreboard>,
+    pub call_graph : CallGraph,
+    pub sentence_path : Vec<String>,
+    pub current_function : Option<String>,
+    pub frame : Vec<String>,
+    pub mc_version : McVersion,
+    pub in_macro : bool,
+    pub float_precision : u32,
+    pub overflow : Overflow,
+    pub std_functions : HashMap<String, MCFunction>,
+    pub features : HashSet<String>,
 }
 
 #[derive(Debug)]
@@ -103,6 +266,29 @@ enum Line {
 #[derive(Clone, Debug)]
 pub enum SentenceError {
     UnnamedFunction,
+    UnnamedStructure,
+    UnnamedEnumeration,
+    InvalidParameter(String),
+    InvalidStructureField(String),
+    InvalidEnumerationVariant(String),
+    InvalidMatchArm(String),
+    UnmatchableType(scoreboard::Types),
+    DuplicatedMatchArm(String),
+    NonExhaustiveMatch(Vec<String>),
+    MacroNotSupported(McVersion),
+    UnknownVersion(String),
+    InvalidFloatPrecision(u32),
+    UnknownAttribute(String),
+    UnknownOverflowMode(String),
+    CheckedOverflowNotSupported(McVersion),
+    StatementAfterReturn(McVersion),
+    UnknownEvent(String),
+    InvalidEventHandler(String),
+    InvalidDuration(String),
+    InvalidWait(String),
+    EveryOutsideOfTopLevel(String),
+    InvalidCfg(String),
+    TypeErrors(Vec<String>),
     SentenceHasNoStartIdentifier,
     SentenceDoesntEndWithEndSpecifier,
     InvalidFormula(EvaluateError)
@@ -112,12 +298,58 @@ impl fmt::Display for SentenceError {
         write!(f, "{}", match CURRENT_LANGUAGE {
             Language::English => match self {
                 Self::UnnamedFunction => "A function must has a name.".to_string(),
+                Self::UnnamedStructure => "A structure must has a name.".to_string(),
+                Self::UnnamedEnumeration => "An enumeration must has a name.".to_string(),
+                Self::InvalidParameter(p) => format!("{} is not a valid parameter. It must be like name:type.", p),
+                Self::InvalidStructureField(f) => format!("{} is not a valid field. It must be like name:type, and be unique.", f),
+                Self::InvalidEnumerationVariant(v) => format!("{} is not a valid variant. It must be a unique name.", v),
+                Self::InvalidMatchArm(a) => format!("{} is not a valid arm of match. It must be like pattern => {{...}}.", a),
+                Self::UnmatchableType(t) => format!("A value of {} type can't be matched. Only int and enums can be.", t),
+                Self::DuplicatedMatchArm(p) => format!("The pattern {} is matched more than once.", p),
+                Self::NonExhaustiveMatch(missing) => format!("The match is not exhaustive. {} is not covered.", missing.join(", ")),
+                Self::MacroNotSupported(v) => format!("Minecraft {} doesn't support function macros. {} or later is needed.", v, McVersion::MACRO),
+                Self::UnknownVersion(v) => format!("{} couldn't be solved as a version of Minecraft.", v),
+                Self::InvalidFloatPrecision(p) => format!("{} is not a valid precision of float. It must be {} or less.", p, scoreboard::float::MAX_PRECISION),
+                Self::UnknownAttribute(a) => format!("#[{}] is not a known attribute.", a),
+                Self::UnknownOverflowMode(m) => format!("{} is not an overflow mode. It must be checked, saturating or wrapping.", m),
+                Self::CheckedOverflowNotSupported(v) => format!("Minecraft {} can't stop a function by a runtime error, so checked overflow needs {} or later.", v, McVersion::RETURN_FAIL),
+                Self::StatementAfterReturn(v) => format!("Minecraft {} can't leave a function in the middle, so nothing can follow a return. It needs {} or later.", v, McVersion::RETURN_FAIL),
+                Self::UnknownEvent(e) => format!("{} is not a known event.", e),
+                Self::InvalidEventHandler(h) => format!("{} is not a valid event handler. It must be like on event fn name() {{...}}, without parameters.", h),
+                Self::InvalidDuration(d) => format!("{} is not a valid duration. It must be like 20t, 5s or 1d, optionally followed by as <selector>.", d),
+                Self::InvalidWait(w) => format!("{} can be used only directly inside of a function which returns nothing and isn't a macro.", w),
+                Self::EveryOutsideOfTopLevel(e) => format!("{} {{...}} can be used only on the top level.", e),
+                Self::InvalidCfg(c) => format!("{} is not a valid condition of cfg. It must be like feature = \"name\" or mc_version >= \"1.20.5\", combined by not, all or any.", c),
+                Self::TypeErrors(errors) => format!("{} error(s) found while checking types.\n{}", errors.len(), errors.join("\n")),
                 Self::SentenceHasNoStartIdentifier => "A sentence must has a {.".to_string(),
                 Self::SentenceDoesntEndWithEndSpecifier => "A sentence must ends with }.".to_string(),
                 Self::InvalidFormula(e) => format!("Error(s) occured while evaluating a formula. Detail => {}", e)
             },
             Language::Japanese => match self {
                 Self::UnnamedFunction => "関数は名前を持たなければなりません。".to_string(),
+                Self::UnnamedStructure => "構造体は名前を持たなければなりません。".to_string(),
+                Self::UnnamedEnumeration => "列挙型は名前を持たなければなりません。".to_string(),
+                Self::InvalidParameter(p) => format!("{}は無効な引数です。名前:型の形式で宣言してください。", p),
+                Self::InvalidStructureField(f) => format!("{}は無効なフィールドです。名前:型の形式で、重複なく宣言してください。", f),
+                Self::InvalidEnumerationVariant(v) => format!("{}は無効な列挙子です。重複しない名前で宣言してください。", v),
+                Self::InvalidMatchArm(a) => format!("{}はmatchの分岐として無効です。パターン => {{...}}の形式で記述してください。", a),
+                Self::UnmatchableType(t) => format!("{}型の値はmatchできません。matchできるのはintと列挙型のみです。", t),
+                Self::DuplicatedMatchArm(p) => format!("パターン{}が複数回matchされています。", p),
+                Self::NonExhaustiveMatch(missing) => format!("matchが網羅的ではありません。{}が考慮されていません。", missing.join(", ")),
+                Self::MacroNotSupported(v) => format!("Minecraft {}は関数マクロに対応していません。{}以降が必要です。", v, McVersion::MACRO),
+                Self::UnknownVersion(v) => format!("{}をMinecraftのバージョンとして処理できませんでした。", v),
+                Self::InvalidFloatPrecision(p) => format!("{}はfloatの精度として無効です。{}以下で指定してください。", p, scoreboard::float::MAX_PRECISION),
+                Self::UnknownAttribute(a) => format!("#[{}]は未知の属性です。", a),
+                Self::UnknownOverflowMode(m) => format!("{}はオーバーフローの処理方法ではありません。checked、saturating、wrappingのいずれかを指定してください。", m),
+                Self::CheckedOverflowNotSupported(v) => format!("Minecraft {}は実行時エラーで関数を停止できないため、checkedなオーバーフローには{}以降が必要です。", v, McVersion::RETURN_FAIL),
+                Self::StatementAfterReturn(v) => format!("Minecraft {}は関数を途中で抜けられないため、returnの後に文を書けません。{}以降が必要です。", v, McVersion::RETURN_FAIL),
+                Self::UnknownEvent(e) => format!("{}は未知のイベントです。", e),
+                Self::InvalidEventHandler(h) => format!("{}は無効なイベントハンドラです。on イベント fn 名前() {{...}}の形式で、引数なしで宣言してください。", h),
+                Self::InvalidDuration(d) => format!("{}は無効な時間です。20t、5s、1dのように指定し、必要に応じてas <セレクター>を続けてください。", d),
+                Self::InvalidWait(w) => format!("{}は、値を返さずマクロでもない関数の直下でのみ使用できます。", w),
+                Self::EveryOutsideOfTopLevel(e) => format!("{} {{...}}はトップレベルでのみ使用できます。", e),
+                Self::InvalidCfg(c) => format!("{}はcfgの条件として無効です。feature = \"名前\"やmc_version >= \"1.20.5\"の形式で記述し、not、all、anyで組み合わせてください。", c),
+                Self::TypeErrors(errors) => format!("型検査中に{}個のエラーが見つかりました。\n{}", errors.len(), errors.join("\n")),
                 Self::SentenceHasNoStartIdentifier => "文は{を持たなければなりません。".to_string(),
                 Self::SentenceDoesntEndWithEndSpecifier => "文は}で終了しなければなりません。".to_string(),
                 Self::InvalidFormula(e) => format!("式の評価中にエラーが発生しました。詳細 => {}", e)
@@ -127,34 +359,278 @@ impl fmt::Display for SentenceError {
 }
 struct Sentence {
     pub name : String,
+    pub docs : Vec<String>,
+    pub attributes : Vec<String>,
+    pub event : Option<Event>,
     pub specifiers : Vec<String>,
     pub parsed_lines : Vec<Line>
 }
 
-/// This is a function to generate expected length random charactors.
+/// The name of the function made from the top level of a source code.
+pub const ROOT_FUNCTION:&str = "mcpp_main";
+
+/// The pure function to name an anonymous sentence from its parent and its position, like if_3 or count_while_0.
 /// 
-/// The length will be defined by the argument, length.
-fn generate_random_string(length: usize) -> String {
-    use rand::Rng;
-    const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyz\
-                            ABCDEFGHIJKLMNOPQRSTUVWXYZ\
-                            0123456789";
-    let mut rng = rand::thread_rng();
+/// The keyword is the leading word of the sentence, and a bare block is named as block.
+/// Sentences on the top level are named without the parent, so builds of the same source always have the same names.
+fn anonymous_name(parent:&str, sentence:&str, index:usize) -> String {
+    let mut keyword = sentence
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect::<String>()
+        .to_lowercase();
+    if keyword.is_empty() {
+        keyword = "block".to_string();
+    }
+    if parent == ROOT_FUNCTION {
+        format!("{}_{}", keyword, index)
+    } else {
+        format!("{}_{}_{}", parent, keyword, index)
+    }
+}
 
-    let random_string: String = (0..length)
-        .map(|_| {
-            let idx = rng.gen_range(0..CHARSET.len());
-            CHARSET[idx] as char
-        })
-        .collect();
+/// The signature of a function, like `fn name(a:int, b:Vec3) -> Vec3`.
+/// 
+/// Types are kept as annotations because they are solved while compiling.
+struct Signature {
+    pub name : String,
+    pub params : Vec<(String, String)>,
+    pub ret_type : Option<String>
+}
 
-    random_string
+impl Signature {
+    fn parse(specifier:&str) -> Result<Signature, SentenceError> {
+        let without_macro = specifier.trim();
+        let without_macro = without_macro.strip_prefix("macro").unwrap_or(without_macro).trim();
+        let without_fn = without_macro.strip_prefix("fn").unwrap_or(without_macro).trim();
+        let (head, ret_type) = match without_fn.split_once("->") {
+            Some(s) => (s.0.trim(), Some(s.1.trim().to_string())),
+            None => (without_fn, None)
+        };
+        let (name, params) = match head.split_once('(') {
+            Some(s) => match s.1.trim_end().strip_suffix(')') {
+                Some(p) => (s.0.trim(), p),
+                None => return Err(SentenceError::InvalidParameter(s.1.to_string()))
+            },
+            None => (head, "")
+        };
+        if name.is_empty() {
+            return Err(SentenceError::UnnamedFunction)
+        }
+        let mut parsed_params:Vec<(String, String)> = Vec::new();
+        for param in params.split(',').map(|f| f.trim()).filter(|f| !f.is_empty()) {
+            match param.split_once(':') {
+                Some(s) if !s.0.trim().is_empty() => parsed_params.push(
+                    (s.0.trim().to_string(), s.1.trim().to_string())
+                ),
+                _ => return Err(SentenceError::InvalidParameter(param.to_string()))
+            }
+        }
+        Ok(
+            Signature {
+                name : name.to_string(),
+                params : parsed_params,
+                ret_type : ret_type.filter(|f| !f.is_empty())
+            }
+        )
+    }
 }
 
 impl Sentence {
+    /// Split the inside of a sentence onto lines.
+    /// 
+    /// A line ends with ; or } which closes a sentence.
+    /// 
+    /// Commas between sentences, like arms of match, are ignored.
+    /// 
+    /// Characters in string literals are never treated as delimiters.
+    /// 
+    /// `//` and `/* */` comments are removed, and a `#` comment ends at the end of its line, so it never swallows the next statement.
+    /// `///` doc comments are kept on the head of the next line, then split off by [Sentence::split_docs].
+    fn split_lines(inside:&str) -> Vec<String> {
+        let mut lines:Vec<String> = Vec::new();
+        let mut current = String::new();
+        let mut depth = 0;
+        let mut in_string = false;
+        let mut escaped = false;
+        // Whether only whitespaces are found since the last new line.
+        let mut line_head = true;
+        let mut i = 0;
+        while let Some(c) = inside[i..].chars().next() {
+            let rest = &inside[i..];
+            i += c.len_utf8();
+            if in_string {
+                current.push(c);
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => ()
+                }
+                continue;
+            }
+            let was_line_head = line_head;
+            line_head = c == '\n' || (line_head && c.is_whitespace());
+            let is_hash_comment = c == '#' && was_line_head && !rest.starts_with("#[");
+            if rest.starts_with("//") || rest.starts_with("/*") || is_hash_comment {
+                // Inside of a comment is skipped at once, so braces and quotes in it are never counted.
+                let length = match rest.strip_prefix("/*") {
+                    Some(block) => block.find("*/").map(|f| f + 4).unwrap_or(rest.len()),
+                    None => rest.find('\n').unwrap_or(rest.len())
+                };
+                let comment = &rest[..length];
+                i += length - c.len_utf8();
+                if rest.starts_with("/*") {
+                    current.push(' ');
+                    line_head = was_line_head;
+                } else if (comment.starts_with("///") && !comment.starts_with("////")) || (is_hash_comment && depth != 0) {
+                    current.push_str(comment);
+                } else if is_hash_comment {
+                    if !current.trim().is_empty() {
+                        lines.push(current.clone());
+                        current.clear();
+                    }
+                    lines.push(comment.to_string());
+                }
+                continue;
+            }
+            match c {
+                '"' => {
+                    in_string = true;
+                    current.push(c);
+                },
+                '{' => {
+                    depth += 1;
+                    current.push(c);
+                },
+                '}' => {
+                    depth -= 1;
+                    current.push(c);
+                    if depth == 0 {
+                        lines.push(current.clone());
+                        current.clear();
+                    }
+                },
+                ';' if depth == 0 => {
+                    lines.push(current.clone());
+                    current.clear();
+                },
+                _ => current.push(c)
+            }
+        }
+        lines.push(current);
+        lines
+            .iter()
+            .map(|f| f.trim().trim_start_matches(',').trim().to_string())
+            .filter(|f| !f.is_empty())
+            .collect()
+    }
+    /// Split `///` doc comments off the head of a line.
+    fn split_docs(raw:&str) -> (Vec<String>, &str) {
+        let mut docs:Vec<String> = Vec::new();
+        let mut rest = raw.trim_start();
+        while let Some(doc) = rest.strip_prefix("///") {
+            let (line, others) = doc.split_once('\n').unwrap_or((doc, ""));
+            docs.push(line.strip_prefix(' ').unwrap_or(line).trim_end().to_string());
+            rest = others.trim_start();
+        }
+        (docs, rest)
+    }
+    fn warn_ignored_docs(docs:&[String]) {
+        if !docs.is_empty() {
+            println!("⚠️  A doc comment is put on something other than a function, so it is ignored. => /// {}", docs.join(" "));
+        }
+    }
+    fn is_function(&self) -> bool {
+        match self.specifiers.first().map(|f| f.as_str()) {
+            Some("fn") => true,
+            Some("macro") => self.specifiers.get(1).is_some_and(|f| f == "fn"),
+            _ => false
+        }
+    }
+    fn is_macro(&self) -> bool {
+        self.specifiers.first().is_some_and(|f| f == "macro")
+    }
+    fn is_structure(&self) -> bool {
+        self.specifiers.first().is_some_and(|f| f == "struct")
+    }
+    fn is_enumeration(&self) -> bool {
+        self.specifiers.first().is_some_and(|f| f == "enum")
+    }
+    fn is_match(&self) -> bool {
+        self.specifiers.first().is_some_and(|f| f == "match")
+    }
+    fn is_every(&self) -> bool {
+        self.specifiers.first().is_some_and(|f| f == "every")
+    }
+    /// Get the specifier of `after 20t {...}`, if the sentence is scheduled.
+    fn get_after(&self) -> Result<Option<After>, SentenceError> {
+        match self.specifiers.first() {
+            Some(s) if s == "after" => Ok(Some(After::parse(&self.specifiers[1..])?)),
+            _ => Ok(None)
+        }
+    }
+    /// Whether the sentence can leave its function early by return or checked overflow, when its parent calls it.
+    /// 
+    /// Functions and scheduled sentences run on their own, so they never make the parent leave.
+    fn can_exit(&self, overflow:Overflow) -> bool {
+        if self.is_function() || self.is_every() || self.specifiers.first().is_some_and(|f| f == "after") {
+            return false
+        }
+        let overflow = self.get_overflow().ok().flatten().unwrap_or(overflow);
+        self.parsed_lines.iter().any(|f| match f {
+            Line::Formula(f) => overflow == Overflow::Checked || f.trim().starts_with("return "),
+            Line::Sentence(s) => s.can_exit(overflow),
+            Line::Comment(_) => false
+        })
+    }
+    /// Whether given lines refer to the executor, like @s or relative coordinates.
+    fn uses_executor(lines:&[Line]) -> bool {
+        lines.iter().any(|f| match f {
+            Line::Formula(f) => ["@s", "~", "^"].iter().any(|p| f.contains(p)),
+            Line::Sentence(s) => s.specifiers.iter().any(|f| f.contains("@s")) || Sentence::uses_executor(&s.parsed_lines),
+            Line::Comment(_) => false
+        })
+    }
+    /// Whether given lines refer to a variable of given name, like `x` but not `a.x` or `xs`.
+    fn mentions(lines:&[Line], name:&str) -> bool {
+        let is_word = |c:char| c.is_alphanumeric() || c == '_';
+        let found = |text:&str| text.match_indices(name).any(|(i, _)| {
+            !text[..i].ends_with(|c| is_word(c) || c == '.') && !text[i + name.len()..].starts_with(is_word)
+        });
+        lines.iter().any(|f| match f {
+            Line::Formula(f) => found(f),
+            Line::Sentence(s) => s.specifiers.iter().any(|f| found(f)) || Sentence::mentions(&s.parsed_lines, name),
+            Line::Comment(_) => false
+        })
+    }
+    /// Check `wait(...)` can be used in the sentence, then return the duration of it.
+    /// 
+    /// The rest of the function is run later, so nothing can be returned to the caller,
+    /// and arguments of a macro can't be given to the rest.
+    fn check_wait(&self, formula:&str, duration:Result<String, SentenceError>) -> Result<String, SentenceError> {
+        let returns_nothing = Signature::parse(&self.specifiers.join(" ")).is_ok_and(|f| f.ret_type.is_none());
+        if self.is_function() && !self.is_macro() && returns_nothing {
+            duration
+        } else {
+            Err(SentenceError::InvalidWait(formula.to_string()))
+        }
+    }
+    /// Get formulas in the sentence, which are fields of a structure or variants of an enumeration.
+    fn get_formulas(&self) -> Vec<String> {
+        self.parsed_lines
+            .iter()
+            .filter_map(|f| match f {
+                Line::Formula(f) => Some(f.clone()),
+                _ => None
+            })
+            .collect()
+    }
     fn guess_line_syntax(input:&str) -> SyntaxType {
         let trimed = input.trim();
-        if trimed.starts_with('#') { SyntaxType::Comment }
+        if trimed.starts_with("#[") && trimed.ends_with('}') { SyntaxType::Sentence }
+        else if trimed.starts_with("#[") { SyntaxType::Formula }
+        else if trimed.starts_with('#') { SyntaxType::Comment }
         else if trimed.ends_with('}') { SyntaxType::Sentence }
         else { SyntaxType::Formula }
     }
@@ -173,48 +649,131 @@ impl Sentence {
         }
         Ok((specifier, inside))
     }
+    /// Split attributes like #[name(argument)] off the head of a sentence.
+    fn split_attributes(raw:&str) -> (Vec<String>, &str) {
+        let mut attributes:Vec<String> = Vec::new();
+        let mut rest = raw.trim_start();
+        while let Some((attribute, others)) = rest.strip_prefix("#[").and_then(|f| f.split_once(']')) {
+            attributes.push(attribute.trim().to_string());
+            rest = others.trim_start();
+        }
+        (attributes, rest)
+    }
+    /// Get the argument of an attribute like #[name(argument)], if the sentence has it.
+    fn get_attribute(&self, name:&str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .filter_map(|f| f.split_once('('))
+            .find(|(attribute, _)| attribute.trim() == name)
+            .map(|(_, argument)| argument.trim_end_matches(')').trim())
+    }
+    /// Get the overflow mode given by #[overflow(...)], if the sentence has it.
+    fn get_overflow(&self) -> Result<Option<Overflow>, SentenceError> {
+        let is_known = |attribute:&String| attribute.split('(').next().is_some_and(|f| ["overflow", "cfg"].contains(&f.trim()));
+        if let Some(unknown) = self.attributes.iter().find(|f| !is_known(f)) {
+            return Err(SentenceError::UnknownAttribute(unknown.clone()))
+        }
+        match self.get_attribute("overflow") {
+            Some(mode) => match Overflow::from_name(mode) {
+                Some(o) => Ok(Some(o)),
+                None => Err(SentenceError::UnknownOverflowMode(mode.to_string()))
+            },
+            None => Ok(None)
+        }
+    }
+    /// Remove lines and sentences whose `#[cfg(...)]` doesn't hold, from the sentence and the inside of it.
+    /// 
+    /// Only cfg can be put on a formula.
+    fn apply_cfg(&mut self, features:&HashSet<String>, mc_version:McVersion) -> Result<(), SentenceError> {
+        let mut lines:Vec<Line> = Vec::new();
+        for line in self.parsed_lines.drain(..) {
+            match line {
+                Line::Formula(f) => {
+                    let (attributes, formula) = Self::split_attributes(&f);
+                    if let Some(unknown) = attributes.iter().find(|f| !f.starts_with("cfg")) {
+                        return Err(SentenceError::UnknownAttribute(unknown.clone()))
+                    }
+                    if cfg::is_enabled(&attributes, features, mc_version)? {
+                        lines.push(Line::Formula(formula.to_string()));
+                    }
+                },
+                Line::Sentence(mut s) => if cfg::is_enabled(&s.attributes, features, mc_version)? {
+                    s.apply_cfg(features, mc_version)?;
+                    lines.push(Line::Sentence(s));
+                },
+                comment => lines.push(comment)
+            }
+        }
+        self.parsed_lines = lines;
+        Ok(())
+    }
+    /// Parse a source code onto a sentence, which is named as the top level.
     pub fn onto_sentence(raw:&str) -> Result<Sentence, SentenceError> {
+        Self::onto_named_sentence(raw, ROOT_FUNCTION)
+    }
+    /// Parse a sentence, which is named by given name unless it declares a name itself.
+    fn onto_named_sentence(raw:&str, anonymous:&str) -> Result<Sentence, SentenceError> {
+        let (docs, raw) = Self::split_docs(raw);
+        let (attributes, raw) = Self::split_attributes(raw);
         // Split a given sentence onto a specialiser and a inside.
         let splitted = match Sentence::split_sentence(raw) {
             Ok(o) => o,
             Err(e) => return Err(e)
         };
+        let (event, specifier) = Event::split_off(splitted.0)?;
         // Split a given specialiser onto tokens.
-        let tokenized_specializer:Vec<&str> = splitted
-            .0
+        let tokenized_specializer:Vec<&str> = specifier
             .split_whitespace()
             .filter(|f| !f.is_empty())
             .collect();
-        let parsed = splitted
-            .1
-            .replace("}", "};")
-            .split(";")
-            .map(|f| f.trim())
-            .filter(|f| !f.is_empty() )
-            .map(|f| f.to_string())
-            .collect::<Vec<String>>();
+        let parsed = Self::split_lines(&splitted.1);
         let name:String = match tokenized_specializer.get(0) {
-            None => generate_random_string(30),
+            None => anonymous.to_string(),
             Some(s) => match *s {
-                "fn" => match tokenized_specializer.get(1) {
+                "fn" | "macro" => Signature::parse(specifier)?.name,
+                "struct" => match tokenized_specializer.get(1) {
+                    Some(s) => s.to_string(),
+                    None => return Err(SentenceError::UnnamedStructure)
+                },
+                "enum" => match tokenized_specializer.get(1) {
                     Some(s) => s.to_string(),
-                    None => return Err(SentenceError::UnnamedFunction)
+                    None => return Err(SentenceError::UnnamedEnumeration)
                 },
-                _ => generate_random_string(32)
+                _ => anonymous.to_string()
             }
         };
+        // A handler is called by an advancement, so nothing can be passed to it.
+        if event.is_some() && (tokenized_specializer.first() != Some(&"fn") || !Signature::parse(specifier)?.params.is_empty()) {
+            return Err(SentenceError::InvalidEventHandler(splitted.0.to_string()))
+        }
+        if !matches!(tokenized_specializer.first(), Some(&"fn") | Some(&"macro")) {
+            Self::warn_ignored_docs(&docs);
+        }
         let mut lines:Vec<Line> = Vec::new();
-        for line in &parsed {
-            let line = match Self::guess_line_syntax(&line) {
-                SyntaxType::Comment => Line::Comment(line.clone()),
-                SyntaxType::Formula => Line::Formula(line.clone()),
-                SyntaxType::Sentence => Line::Sentence(Self::onto_sentence(&line)?)
+        for raw_line in &parsed {
+            // Doc comments are split off by the sentence itself, so they are kept for sentences.
+            let (line_docs, line) = Self::split_docs(raw_line);
+            let syntax = Self::guess_line_syntax(line);
+            if !matches!(syntax, SyntaxType::Sentence) {
+                Self::warn_ignored_docs(&line_docs);
+            }
+            let line = match syntax {
+                _ if line.is_empty() => continue,
+                SyntaxType::Comment => Line::Comment(line.to_string()),
+                SyntaxType::Formula => Line::Formula(line.to_string()),
+                SyntaxType::Sentence => Line::Sentence(Self::onto_named_sentence(
+                    raw_line,
+                    &anonymous_name(&name, Self::split_attributes(line).1, lines.len())
+                )?)
             };
             lines.push(line);
         }
         Ok(
             Sentence {
                 name : name,
+                docs : docs,
+                attributes : attributes,
+                event : event,
                 specifiers : tokenized_specializer
                     .iter()
                     .map(|f| f.to_string())
@@ -223,13 +782,19 @@ impl Sentence {
             }
         )
     }
+    /// Compile the sentence as a slave of given compiler, then return the callment of it.
+    /// 
+    /// Functions declared by `fn` are only registered, so the callment will be empty.
     fn compile_then_call(&self, compiler:&mut CompileTask, namespace:&str) -> Result<String, SentenceError> {
-        let mut slave_compiler = compiler.clone();
+        let mut slave_compiler = compiler.spawn_slave();
         match slave_compiler.compile_sentence(self, namespace) {
             Ok(o) => {
                 let compiled_name = o.name.clone();
                 compiler.local_functions.insert(compiled_name.clone(), o);
-                Ok(compiler.get_function(&compiled_name).unwrap().callment.clone())
+                Ok(
+                    if self.is_function() { String::new() }
+                    else { compiler.get_function(&compiled_name).unwrap().callment.clone() }
+                )
             },
             Err(e) => Ok(
                 format!(
@@ -251,24 +816,109 @@ impl Sentence {
 
 impl CompileTask {
     fn compile_sentence(&mut self, sentence:&Sentence, namespace:&str) -> Result<MCFunction, SentenceError> {
-        let mut res:Vec<String> = Vec::new();
+        // Doc comments of a function are put on the head of it.
+        let mut res:Vec<String> = if sentence.is_function() {
+            sentence.docs.iter().map(|f| format!("# {}", f).trim_end().to_string()).collect()
+        } else {
+            Vec::new()
+        };
         println!("Now compiling {}...", sentence.name);
+        self.sentence_path.push(sentence.name.clone());
 
-        for line in &sentence.parsed_lines {
-            let compiled  =match line {
-                Line::Formula(f) => self.eval_line(&f),
-                Line::Sentence(s) => s.compile_then_call(self, namespace)?,
+        let mut args:Vec<Scoreboard> = Vec::new();
+        let mut ret_container = Scoreboard {
+            name  : format!("TEMP.RETURN_VALUE.{}", sentence.name),
+            data_type : scoreboard::Types::Non,
+            scope : Vec::new()
+        };
+        if sentence.is_function() {
+            let signature = Signature::parse(&sentence.specifiers.join(" "))?;
+            (args, ret_container) = self.solve_signature(&signature)?;
+            self.current_function = Some(call_graph::key(&self.sentence_path));
+            self.frame.clear();
+            for arg in &args {
+                self.declare_variable(&arg.name, arg.clone());
+            }
+            self.ret_container = Some(ret_container.clone());
+        }
+        let after = sentence.get_after()?;
+        match &after {
+            Some(a) if a.carried.is_some() => res.push(format!("tag @s remove {}", After::tag(&sentence.name))),
+            Some(a) if Sentence::uses_executor(&sentence.parsed_lines) => println!(
+                "⚠️  after {} {{...}} uses the executor, but a scheduled function runs without it. Use after {} as @s {{...}} to carry it along.",
+                a.delay,
+                a.delay
+            ),
+            _ => ()
+        }
+        if sentence.is_every() && Sentence::uses_executor(&sentence.parsed_lines) {
+            println!("⚠️  {} {{...}} uses the executor, but it runs from the tick function without one.", sentence.specifiers.join(" "));
+        }
+        // The advancement of an event is revoked at first, so the event can fire again while the handler runs.
+        if sentence.event.as_ref().is_some_and(|f| f.revokes()) {
+            res.push(format!("advancement revoke @s only {}", mcfunction::get_id(namespace, &self.scope, &sentence.name)));
+        }
+        // Only the function macro itself can use $(...), even sentences inside of it are compiled onto other functions.
+        self.in_macro = sentence.is_macro();
+        self.apply_overflow(sentence)?;
+        self.declare_items(sentence, namespace)?;
+
+        // The function is split onto parts at each wait, and parts other than the first are continuations.
+        let mut parts:Vec<Vec<String>> = Vec::new();
+        let mut continuations:Vec<MCFunction> = Vec::new();
+        // Callments of every sentences by their periods, which are run from the tick function.
+        let mut periodic:BTreeMap<u32, Vec<String>> = BTreeMap::new();
+        for (i, line) in sentence.parsed_lines.iter().enumerate() {
+            let compiled = match line {
+                Line::Formula(f) => match schedule::parse_wait(f) {
+                    Some(duration) => {
+                        let delay = sentence.check_wait(f, duration)?;
+                        if Sentence::uses_executor(&sentence.parsed_lines[i + 1..]) {
+                            println!("⚠️  Lines after {} use the executor, but they run without it.", f);
+                        }
+                        let continuation = MCFunction::generate(
+                            &schedule::continuation_name(&sentence.name, continuations.len() + 1),
+                            String::new(),
+                            namespace,
+                            &self.scope
+                        );
+                        let mut variables = self.local_variables
+                            .iter()
+                            .filter(|f| !matches!(f.1.data_type, scoreboard::Types::Struct(_)))
+                            .collect::<Vec<(&String, &Scoreboard)>>();
+                        variables.sort_by(|a, b| a.0.cmp(b.0));
+                        res.append(&mut schedule::suspend(&continuation, &delay, &variables));
+                        parts.push(std::mem::replace(&mut res, schedule::resume(&continuation, &variables)));
+                        continuations.push(continuation);
+                        continue
+                    },
+                    None => self.eval_line(&f)
+                },
+                Line::Sentence(s) if s.is_structure() || s.is_enumeration() => continue,
+                Line::Sentence(s) if s.is_match() => {
+                    let callment = s.compile_match(self, namespace)?;
+                    self.propagate_exit(s, callment)
+                },
+                Line::Sentence(s) if s.is_every() => {
+                    let callment = s.compile_then_call(self, namespace)?;
+                    periodic.entry(schedule::parse_every(&s.specifiers[1..])?).or_default().push(callment);
+                    continue
+                },
+                Line::Sentence(s) => {
+                    let callment = s.compile_then_call(self, namespace)?;
+                    self.propagate_exit(s, callment)
+                },
                 Line::Comment(c) => c.clone()
             };
-            res.push(compiled);
-        }
-        let pure_callment = format!(
-            "function {}:{}{}{}",
-            namespace,
-            self.scope.clone().join("/"),
-            if self.scope.is_empty() {"/"} else {""},
-            sentence.name
-        );
+            if !compiled.is_empty() {
+                res.push(compiled);
+            }
+        }
+        let pure_callment = if sentence.is_macro() {
+            mcfunction::get_macro_callment(namespace, &self.scope, &sentence.name)
+        } else {
+            mcfunction::get_callment(namespace, &self.scope, &sentence.name)
+        };
         let callment = match sentence.specifiers.get(0) {
             Some(s) => match s.as_str() {
                 "if" => Ok(
@@ -289,34 +939,204 @@ impl CompileTask {
         // Free variables
         if !&self.local_variables.is_empty() {
             res.push("\n# Free all of local variables".to_string());
-            for var in &self.local_variables {
-                res.push(var.1.free());
+            // Fields of a structure are registered as local variables too.
+            let mut variables = self.local_variables
+                .iter()
+                .filter(|f| !matches!(f.1.data_type, scoreboard::Types::Struct(_)))
+                .collect::<Vec<(&String, &Scoreboard)>>();
+            variables.sort_by(|a, b| a.0.cmp(b.0));
+            for (_, var) in variables {
+                res.push(var.free());
             }
         }
+        // A block which reached its end clears the exit flag, even if a function called from it has set it.
+        if sentence.can_exit(self.overflow) && self.mc_version >= McVersion::RETURN_FAIL {
+            res.push(scoreboard::get_exit_flag().pure_assign_value(0));
+        }
+        parts.push(res);
+        let mut parts = parts.into_iter().map(|f| f.join("\n"));
+        let inside = parts.next().unwrap_or_default();
+        for (continuation, part) in continuations.iter_mut().zip(parts) {
+            continuation.inside = part;
+        }
+        if !periodic.is_empty() {
+            continuations.push(schedule::generate_periodic(&periodic, namespace, &self.scope)?);
+        }
         println!("Compiling of {} ended successfully!", sentence.name);
-        Ok(
-            MCFunction {
-                name : sentence.name.to_string(),
-                inside : res.join("\n"),
-                namespace : namespace.to_string(),
-                callment : callment,
-                child_func : self.local_functions
-                    .to_owned()
-                    .into_iter()
-                    .map(|f| f.1)
-                    .collect::<Vec<MCFunction>>(),
-                scope : self.scope.clone(),
+        // Functions are sorted, so the order of them never depends on the hash map.
+        let mut child_func = self.local_functions.values().cloned().collect::<Vec<MCFunction>>();
+        child_func.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut compiled = MCFunction {
+            name : sentence.name.to_string(),
+            inside : inside,
+            namespace : namespace.to_string(),
+            callment : callment,
+            child_func : child_func
+                .into_iter()
+                .chain(continuations)
+                .collect::<Vec<MCFunction>>(),
+            scope : self.scope.clone(),
 
-                ret_container : Scoreboard {
-                    name  : format!("TEMP.RETURN_VALUE.{}", sentence.name),
-                    data_type : scoreboard::Types::Non,
-                    scope : Vec::new()
-                }
-            }
+            args : args,
+            ret_container : ret_container,
+            is_macro : sentence.is_macro(),
+            event : sentence.event.clone(),
+            tags : Vec::new()
+        };
+        if let Some(after) = after {
+            // Enclosing locals are freed before the scheduled run, so the ones read inside are carried through storage.
+            let mut captured = self.inherited_variables
+                .iter()
+                .filter(|f| !matches!(f.1.data_type, scoreboard::Types::Struct(_)) && Sentence::mentions(&sentence.parsed_lines, f.0))
+                .collect::<Vec<(&String, &Scoreboard)>>();
+            captured.sort_by(|a, b| a.0.cmp(b.0));
+            let carry;
+            (compiled.callment, carry) = after.schedule(&mut compiled, &captured);
+            compiled.child_func.extend(carry);
+        }
+        Ok(compiled)
+    }
+    /// Wrap the callment of a block which can leave the function early, so this sentence leaves as well when the block does.
+    fn propagate_exit(&self, block:&Sentence, callment:String) -> String {
+        if callment.is_empty() || !block.can_exit(self.overflow) || self.mc_version < McVersion::RETURN_FAIL {
+            return callment
+        }
+        let flag = scoreboard::get_exit_flag();
+        format!(
+            "{}\n{}\nexecute if {} run return 0",
+            flag.pure_assign_value(0),
+            callment,
+            flag.pure_match_range("1")
         )
     }
+    /// Set the overflow mode given by the attribute of a sentence, which is inherited by slave compilers.
+    fn apply_overflow(&mut self, sentence:&Sentence) -> Result<(), SentenceError> {
+        if let Some(overflow) = sentence.get_overflow()? {
+            self.overflow = overflow;
+        }
+        if self.overflow == Overflow::Checked && self.mc_version < McVersion::RETURN_FAIL {
+            return Err(SentenceError::CheckedOverflowNotSupported(self.mc_version))
+        }
+        Ok(())
+    }
+    /// Compile a source code.
+    /// 
+    /// The whole code is treated as the inside of an anonymous sentence,
+    /// so it can have some of structures and functions on the top level.
+    /// 
+    /// Types of the whole code are checked before compiling, so no command is emitted from a code with type errors.
     pub fn compile(&mut self, raw:&str, namespace:&str) -> Result<MCFunction, SentenceError> {
-        self.compile_sentence(&Sentence::onto_sentence(raw)?, namespace)
+        let mut root = Sentence::onto_sentence(&format!("{{{}}}", raw))?;
+        root.apply_cfg(&self.features, self.mc_version)?;
+        self.call_graph = CallGraph::new(&root);
+        self.sentence_path.clear();
+        self.std_functions = std_lib::declare(namespace, self.float_precision, self.mc_version)
+            .map_err(SentenceError::InvalidFormula)?;
+        self.check_types(&root)?;
+        let mut compiled = self.compile_sentence(&root, namespace)?;
+        // Functions of the standard library are generated once, even they are called from many places,
+        // and never if they aren't called.
+        let mut std_functions = std_lib::collect_used(&compiled, &self.std_functions);
+        compiled.child_func.append(&mut std_functions);
+        // Objectives and constants are made on load, after every function is generated.
+        let init = init::generate_init(&compiled, namespace);
+        compiled.child_func.push(init);
+        Ok(compiled)
+    }
+    /// Solve types of parameters and a return value of a function.
+    /// 
+    /// Parameters are scoped under the name of the function, like #name.param.
+    fn solve_signature(&self, signature:&Signature) -> Result<(Vec<Scoreboard>, Scoreboard), SentenceError> {
+        let mut args_scope = self.scope.clone();
+        args_scope.push(signature.name.clone());
+        let mut args:Vec<Scoreboard> = Vec::new();
+        for param in &signature.params {
+            args.push(
+                Scoreboard {
+                    name : param.0.clone(),
+                    data_type : match evaluater::to_type(self, &param.1) {
+                        Ok(o) => o,
+                        Err(e) => return Err(SentenceError::InvalidFormula(e))
+                    },
+                    scope : args_scope.clone()
+                }
+            );
+        }
+        let ret_container = Scoreboard {
+            name  : format!("TEMP.RETURN_VALUE.{}", signature.name),
+            data_type : match &signature.ret_type {
+                Some(ret_type) => match evaluater::to_type(self, ret_type) {
+                    Ok(o) => o,
+                    Err(e) => return Err(SentenceError::InvalidFormula(e))
+                },
+                None => scoreboard::Types::Non
+            },
+            scope : Vec::new()
+        };
+        Ok((args, ret_container))
+    }
+    /// Declare enumerations, structures and functions in a sentence before compiling lines of it.
+    /// 
+    /// So functions can be called before their declaration, and can call each other recursively.
+    fn declare_items(&mut self, sentence:&Sentence, namespace:&str) -> Result<(), SentenceError> {
+        let sentences = sentence.parsed_lines
+            .iter()
+            .filter_map(|f| match f {
+                Line::Sentence(s) => Some(s),
+                _ => None
+            })
+            .collect::<Vec<&Sentence>>();
+        for s in sentences.iter().filter(|f| f.is_enumeration()) {
+            let enumeration = Enumeration::parse(&s.name, &s.get_formulas())?;
+            self.enumerations.insert(enumeration.name.clone(), enumeration);
+        }
+        for s in sentences.iter().filter(|f| f.is_structure()) {
+            let structure = Structure::parse(self, &s.name, &s.get_formulas())?;
+            self.structures.insert(structure.name.clone(), structure);
+        }
+        for s in sentences.iter().filter(|f| f.is_function()) {
+            if s.is_macro() && self.mc_version < McVersion::MACRO {
+                return Err(SentenceError::MacroNotSupported(self.mc_version))
+            }
+            let signature = Signature::parse(&s.specifiers.join(" "))?;
+            let mut declared = MCFunction::generate(&signature.name, String::new(), namespace, &self.scope);
+            (declared.args, declared.ret_container) = self.solve_signature(&signature)?;
+            if s.is_macro() {
+                declared.is_macro = true;
+                declared.callment = mcfunction::get_macro_callment(namespace, &self.scope, &signature.name);
+            }
+            self.local_functions.insert(declared.name.clone(), declared);
+        }
+        Ok(())
+    }
+    /// Get variables of the function being compiled, which must be kept around a recursive call.
+    fn get_frame(&self) -> Vec<(&String, &Scoreboard)> {
+        self.frame
+            .iter()
+            .filter_map(|f| self.get_variable(f).map(|v| (f, v)))
+            .filter(|f| !matches!(f.1.data_type, scoreboard::Types::Struct(_)))
+            .collect()
+    }
+    /// Make a slave compiler.
+    /// 
+    /// Local variables and functions of this compiler are inherited by the slave.
+    fn spawn_slave(&self) -> CompileTask {
+        let mut slave = self.clone();
+        slave.inherited_variables.extend(slave.local_variables.drain());
+        slave.inherited_functions.extend(slave.local_functions.drain());
+        slave
+    }
+    /// Declare a local variable.
+    /// 
+    /// If the variable is a structure, each fields are declared as name.field as well.
+    fn declare_variable(&mut self, name:&str, variable:Scoreboard) {
+        for field in variable.fields() {
+            self.declare_variable(&format!("{}.{}", name, field.name), field);
+        }
+        if self.current_function.is_some() && !self.frame.iter().any(|f| f == name) {
+            self.frame.push(name.to_string());
+        }
+        self.local_variables.insert(name.to_string(), variable);
     }
     fn eval_line(&mut self, formula:&str) -> String {
         match evaluater::evaluate(self, formula) {
@@ -338,20 +1158,39 @@ impl CompileTask {
     fn get_function(&self, name:&String) -> Option<&MCFunction> {
         if self.inherited_functions.contains_key(name) { Some(self.inherited_functions.get(name).unwrap()) }
         else if self.local_functions.contains_key(name) { Some(self.local_functions.get(name).unwrap()) }
-        else { None }
+        else { self.std_functions.get(name) }
     }
     fn get_variable(&self, name:&String) -> Option<&Scoreboard> {
-        if self.inherited_variables.contains_key(name) { Some(self.inherited_variables.get(name).unwrap()) }
-        else if self.local_variables.contains_key(name) { Some(self.local_variables.get(name).unwrap()) }
+        if self.local_variables.contains_key(name) { Some(self.local_variables.get(name).unwrap()) }
+        else if self.inherited_variables.contains_key(name) { Some(self.inherited_variables.get(name).unwrap()) }
         else { None }
     }
+    fn get_structure(&self, name:&str) -> Option<&Structure> {
+        self.structures.get(name)
+    }
+    fn get_enumeration(&self, name:&str) -> Option<&Enumeration> {
+        self.enumerations.get(name)
+    }
     pub fn new() -> CompileTask {
         CompileTask {
             scope : Vec::new(),
             inherited_variables : HashMap::new(),
             local_variables : HashMap::new(),
             inherited_functions : HashMap::new(),
-            local_functions : HashMap::new()
+            local_functions : HashMap::new(),
+            structures : HashMap::new(),
+            enumerations : HashMap::new(),
+            ret_container : None,
+            call_graph : CallGraph::default(),
+            sentence_path : Vec::new(),
+            current_function : None,
+            frame : Vec::new(),
+            mc_version : McVersion::default(),
+            in_macro : false,
+            float_precision : scoreboard::float::DEFAULT_PRECISION,
+            overflow : Overflow::default(),
+            std_functions : HashMap::new(),
+            features : HashSet::new()
         }
     }
 }
\ No newline at end of file
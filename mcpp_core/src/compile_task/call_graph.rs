@@ -0,0 +1,170 @@
+//! The call graph of functions, which tells recursive calls.
+//!
+//! # Recursion
+//! Variables are fixed fake players, so a recursive call overwrites variables of the caller.
+//!
+//! The call graph is built before compiling, and when a function calls another function
+//! which can call it back, variables of the function are pushed onto a call stack in data storage
+//! around the callment, then restored. Functions are told apart by the sentences declaring them, not only by their names.
+
+use std::collections::{HashMap, HashSet};
+
+use regex::Regex;
+
+use super::{Line, Scoreboard, Sentence};
+
+/// The data storage used as the call stack. Each frame is a compound appended onto `frames`.
+pub const CALL_STACK:&str = "mcpp:call_stack";
+
+#[test]
+fn call_graph_test() {
+    let root = Sentence::onto_sentence(
+        "{
+            fn even(n:int) -> bool { if n { return odd(n - 1) } }
+            fn odd(n:int) -> bool { if n { return even(n - 1) } }
+            fn fill(n:int) { fill(n - 1) }
+            fn main() { even(10); fill(3) }
+            fn a(n:int) { fn step(n:int) { a(n - 1) } step(n) }
+            fn b(n:int) { fn step(n:int) { } step(n) }
+        }"
+    ).unwrap();
+    let graph = CallGraph::new(&root);
+    let at = |path:&str| format!("{}/{}", root.name, path);
+    assert!(graph.is_recursive(&at("even")));
+    assert!(graph.is_recursive(&at("fill")));
+    assert!(!graph.is_recursive(&at("main")));
+    assert!(graph.is_in_same_cycle(&at("even"), &at("odd")));
+    assert!(!graph.is_in_same_cycle(&at("main"), &at("even")));
+    // Functions of the same name in other sentences are different functions.
+    assert!(graph.is_recursive(&at("a/step")));
+    assert!(!graph.is_recursive(&at("b/step")));
+    let path = [root.name.clone(), "b".to_string()];
+    assert_eq!(graph.resolve(&path, "step"), Some(at("b/step")));
+    assert_eq!(graph.resolve(&path, "fill"), Some(at("fill")));
+}
+
+/// The pure function to get commands to push variables onto the call stack.
+pub fn push_frame(frame:&[(&String, &Scoreboard)]) -> Vec<String> {
+    let mut commands = vec![format!("data modify storage {} frames append value {{}}", CALL_STACK)];
+    for (name, variable) in frame {
+        commands.push(variable.pure_store_to_storage(CALL_STACK, &format!("frames[-1].{}", name)));
+    }
+    commands
+}
+/// The pure function to get commands to restore variables from the call stack, then pop it.
+pub fn pop_frame(frame:&[(&String, &Scoreboard)]) -> Vec<String> {
+    let mut commands:Vec<String> = frame
+        .iter()
+        .map(|(name, variable)| variable.pure_load_from_storage(CALL_STACK, &format!("frames[-1].{}", name)))
+        .collect();
+    commands.push(format!("data remove storage {} frames[-1]", CALL_STACK));
+    commands
+}
+
+/// The pure function to get the key of a function declared at given path of sentences, like root/even.
+pub fn key(path:&[String]) -> String {
+    path.join("/")
+}
+
+/// A call found in a sentence, which is resolved onto a function after every function is found.
+struct Call {
+    caller : String,
+    callee : String,
+    path : Vec<String>
+}
+
+#[derive(Clone, Debug, Default)]
+/// The graph of which function calls which function.
+///
+/// It is built from sentences before compiling,
+/// so functions which are called recursively can be found before their callments are generated.
+/// 
+/// Functions are keyed by the path of sentences declaring them, so functions of the same name in different sentences are not mixed.
+pub struct CallGraph {
+    pub edges : HashMap<String, HashSet<String>>
+}
+
+impl CallGraph {
+    pub(super) fn new(root:&Sentence) -> CallGraph {
+        let mut graph = CallGraph::default();
+        let mut calls:Vec<Call> = Vec::new();
+        graph.collect(root, &mut Vec::new(), None, &mut calls);
+        // Calls of something which is not a function, like a built-in, aren't edges.
+        for call in calls {
+            if let Some(callee) = graph.resolve(&call.path, &call.callee) {
+                graph.edges.entry(call.caller).or_default().insert(callee);
+            }
+        }
+        graph
+    }
+    fn collect(&mut self, sentence:&Sentence, path:&mut Vec<String>, caller:Option<&str>, calls:&mut Vec<Call>) {
+        let call_regex = Regex::new(r"([a-zA-Z_][a-zA-Z0-9_]*)\s*\(").unwrap();
+        // Arms of match are compiled right in the sentence having the match, so the match isn't a part of paths.
+        if !sentence.is_match() {
+            path.push(sentence.name.clone());
+        }
+        let key = key(path);
+        let caller = if sentence.is_function() {
+            self.edges.entry(key.clone()).or_default();
+            Some(key.as_str())
+        } else {
+            caller
+        };
+        let record = |raw:&str, path:&[String], calls:&mut Vec<Call>| {
+            if let Some(caller) = caller {
+                for called in call_regex.captures_iter(raw) {
+                    calls.push(Call { caller : caller.to_string(), callee : called[1].to_string(), path : path.to_vec() });
+                }
+            }
+        };
+        // Parameters of a function look like a call, so only conditions and so on are recorded.
+        if !sentence.is_function() {
+            record(&sentence.specifiers.join(" "), path, calls);
+        }
+        for line in &sentence.parsed_lines {
+            match line {
+                Line::Formula(f) => record(f, path, calls),
+                Line::Sentence(s) => self.collect(s, path, caller, calls),
+                Line::Comment(_) => ()
+            }
+        }
+        if !sentence.is_match() {
+            path.pop();
+        }
+    }
+    /// Get the key of a function called by name from given path of sentences.
+    /// 
+    /// Like the compiler finds functions, the one declared by the nearest sentence is taken.
+    pub fn resolve(&self, path:&[String], name:&str) -> Option<String> {
+        (0..=path.len())
+            .rev()
+            .map(|f| key(&[&path[..f], &[name.to_string()]].concat()))
+            .find(|f| self.edges.contains_key(f))
+    }
+    /// Get whether a function can be reached from another function.
+    fn reaches(&self, from:&str, to:&str) -> bool {
+        let mut visited:HashSet<&str> = HashSet::new();
+        let mut stack:Vec<&str> = vec![from];
+        while let Some(current) = stack.pop() {
+            for next in self.edges.get(current).into_iter().flatten() {
+                if next == to {
+                    return true
+                }
+                if visited.insert(next.as_str()) {
+                    stack.push(next.as_str());
+                }
+            }
+        }
+        false
+    }
+    /// Get whether a function calls itself directly or indirectly.
+    pub fn is_recursive(&self, function:&str) -> bool {
+        self.reaches(function, function)
+    }
+    /// Get whether a call from caller to callee can come back to the caller.
+    ///
+    /// Variables of the caller must be kept on the call stack around such calls.
+    pub fn is_in_same_cycle(&self, caller:&str, callee:&str) -> bool {
+        self.reaches(callee, caller)
+    }
+}
@@ -0,0 +1,145 @@
+//! Conditional compilation.
+//!
+//! `#[cfg(feature = "debug")]` or `#[cfg(mc_version >= "1.20.5")]` put before a sentence or a formula
+//! removes it unless the condition holds. Features are given by the build option.
+
+use std::collections::HashSet;
+
+use super::{McVersion, SentenceError};
+
+#[test]
+fn cfg_test() {
+    let features = HashSet::from(["debug".to_string()]);
+    let version = McVersion::parse("1.20.4").unwrap();
+    let enabled = |raw:&str| Cfg::parse(raw).unwrap().is_enabled(&features, version);
+    assert!(enabled("feature = \"debug\""));
+    assert!(!enabled("feature = \"release\""));
+    assert!(enabled("mc_version >= \"1.20.3\""));
+    assert!(!enabled("mc_version >= \"1.20.5\""));
+    assert!(enabled("not(mc_version < \"1.20\")"));
+    assert!(enabled("all(feature = \"debug\", any(mc_version == \"1.20.4\", feature = \"release\"))"));
+    assert!(Cfg::parse("feature = debug").is_err());
+    assert!(Cfg::parse("mc_version ~= \"1.20\"").is_err());
+
+    let mut compiler = super::CompileTask::new();
+    compiler.features = features;
+    let compiled = compiler.compile(
+        "#[cfg(feature = \"debug\")] print(\"debug\");
+        #[cfg(not(feature = \"debug\"))] print(\"release\");
+        #[cfg(mc_version < \"1.13\")] fn old() { run!(\"say old\") }
+        fn main() { #[cfg(mc_version < \"1.13\")] old() }",
+        "test"
+    ).unwrap();
+    println!("{}", compiled.inside);
+    assert!(compiled.inside.contains("\"text\":\"debug\""));
+    assert!(!compiled.inside.contains("\"text\":\"release\""));
+    assert!(!compiled.child_func.iter().any(|f| f.name == "old"));
+    assert!(super::CompileTask::new().compile("#[cfg(feature = \"debug\")] fn f() { }; f()", "test").is_err());
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// A condition of `#[cfg(...)]`, like `feature = "debug"` or `mc_version >= "1.20.5"`.
+///
+/// Conditions can be combined by `not(...)`, `all(...)` and `any(...)`.
+pub enum Cfg {
+    Feature(String),
+    Version(String, McVersion),
+    Not(Box<Cfg>),
+    All(Vec<Cfg>),
+    Any(Vec<Cfg>)
+}
+
+/// Operators to compare versions. Longer ones come first, so >= is never taken as >.
+const OPERATORS:[&str; 6] = [">=", "<=", "==", "!=", ">", "<"];
+
+/// The pure function to get the inside of a string literal.
+fn unquote(raw:&str) -> Option<&str> {
+    raw.trim().strip_prefix('"')?.strip_suffix('"')
+}
+/// The pure function to split arguments of all(...) or any(...) by commas, which are not in parentheses or strings.
+fn split_arguments(raw:&str) -> Vec<&str> {
+    let mut arguments:Vec<&str> = Vec::new();
+    let (mut depth, mut in_string, mut start) = (0, false, 0);
+    for (i, c) in raw.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                arguments.push(&raw[start..i]);
+                start = i + 1;
+            },
+            _ => ()
+        }
+    }
+    arguments.push(&raw[start..]);
+    arguments.into_iter().filter(|f| !f.trim().is_empty()).collect()
+}
+
+impl Cfg {
+    pub fn parse(raw:&str) -> Result<Cfg, SentenceError> {
+        let raw = raw.trim();
+        let invalid = || SentenceError::InvalidCfg(raw.to_string());
+        for combinator in ["not", "all", "any"] {
+            let Some(inside) = raw
+                .strip_prefix(combinator)
+                .and_then(|f| f.trim_start().strip_prefix('('))
+                .and_then(|f| f.strip_suffix(')'))
+            else { continue };
+            let mut conditions = split_arguments(inside)
+                .into_iter()
+                .map(Cfg::parse)
+                .collect::<Result<Vec<Cfg>, SentenceError>>()?;
+            return match combinator {
+                "not" if conditions.len() == 1 => Ok(Cfg::Not(Box::new(conditions.remove(0)))),
+                "all" => Ok(Cfg::All(conditions)),
+                "any" => Ok(Cfg::Any(conditions)),
+                _ => Err(invalid())
+            }
+        }
+        if let Some(value) = raw.strip_prefix("feature").and_then(|f| f.trim_start().strip_prefix('=')) {
+            return unquote(value).map(|f| Cfg::Feature(f.to_string())).ok_or_else(invalid)
+        }
+        if let Some(comparison) = raw.strip_prefix("mc_version").map(|f| f.trim_start()) {
+            let (operator, version) = OPERATORS
+                .iter()
+                .find_map(|f| comparison.strip_prefix(f).map(|v| (f.to_string(), v)))
+                .ok_or_else(invalid)?;
+            let version = unquote(version).and_then(McVersion::parse).ok_or_else(invalid)?;
+            return Ok(Cfg::Version(operator, version))
+        }
+        Err(invalid())
+    }
+    /// Whether the condition holds with given features and version of Minecraft.
+    pub fn is_enabled(&self, features:&HashSet<String>, mc_version:McVersion) -> bool {
+        match self {
+            Cfg::Feature(f) => features.contains(f),
+            Cfg::Version(operator, version) => match operator.as_str() {
+                ">=" => mc_version >= *version,
+                "<=" => mc_version <= *version,
+                "==" => mc_version == *version,
+                "!=" => mc_version != *version,
+                ">" => mc_version > *version,
+                _ => mc_version < *version
+            },
+            Cfg::Not(c) => !c.is_enabled(features, mc_version),
+            Cfg::All(c) => c.iter().all(|f| f.is_enabled(features, mc_version)),
+            Cfg::Any(c) => c.iter().any(|f| f.is_enabled(features, mc_version))
+        }
+    }
+}
+
+/// Whether every `#[cfg(...)]` in given attributes holds. Attributes without cfg always hold.
+pub fn is_enabled(attributes:&[String], features:&HashSet<String>, mc_version:McVersion) -> Result<bool, SentenceError> {
+    for attribute in attributes {
+        let Some(condition) = attribute
+            .strip_prefix("cfg")
+            .and_then(|f| f.trim_start().strip_prefix('('))
+            .and_then(|f| f.strip_suffix(')'))
+        else { continue };
+        if !Cfg::parse(condition)?.is_enabled(features, mc_version) {
+            return Ok(false)
+        }
+    }
+    Ok(true)
+}
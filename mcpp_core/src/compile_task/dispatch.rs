@@ -0,0 +1,92 @@
+use super::{MCFunction, Scoreboard};
+
+#[test]
+fn dispatch_test() {
+    let score = Scoreboard {
+        name : "state".to_string(),
+        data_type : super::scoreboard::Types::Int,
+        scope : Vec::new()
+    };
+    let branches = (0..10)
+        .map(|i| (i, format!("say {}", i)))
+        .collect::<Vec<(i32, String)>>();
+    let (commands, tree) = dispatch(&score, &branches, "state", "test", &Vec::new());
+    println!("{}", commands.join("\n"));
+    assert_eq!(commands.len(), 2);
+    assert!(commands[0].ends_with("matches 0..4 run function test:state_0_4"));
+    assert!(tree.iter().any(|f| f.name == "state_0_4" && f.inside.contains("matches 0..1 run function test:state_0_1")));
+    assert!(tree.iter().any(|f| f.name == "state_2_4" && f.inside.contains("matches 2 run say 2")));
+    assert_eq!(to_ranges(&[0, 1, 2, 5, 7, 8]), vec!["0..2", "5", "7..8"]);
+}
+
+/// Dispatches with branches fewer than this are written as a flat chain of conditions.
+const DISPATCH_TREE_THRESHOLD:usize = 4;
+
+/// The pure function to compress sorted keys onto ranges, like \[0, 1, 2, 5\] onto \["0..2", "5"\].
+pub fn to_ranges(keys:&[i32]) -> Vec<String> {
+    let mut ranges:Vec<(i32, i32)> = Vec::new();
+    for key in keys {
+        match ranges.last_mut() {
+            Some(last) if last.1 + 1 == *key => last.1 = *key,
+            _ => ranges.push((*key, *key))
+        }
+    }
+    ranges
+        .iter()
+        .map(|f| if f.0 == f.1 { f.0.to_string() } else { format!("{}..{}", f.0, f.1) })
+        .collect()
+}
+
+/// The pure function to run a command corresponding to the value of a scoreboard.
+///
+/// Branches are given as pairs of a key and a command.
+///
+/// If the keys are dense, the dispatch will be a binary-search tree of functions,
+/// so only about log2(N) conditions are checked in runtime.
+///
+/// Commands to start the dispatch will be kept in the first element of tuple,
+/// and functions of the tree, named like name_lo_hi, will be kept in the second element of tuple.
+pub fn dispatch(score:&Scoreboard, branches:&[(i32, String)], name:&str, namespace:&str, scope:&[String]) -> (Vec<String>, Vec<MCFunction>) {
+    let mut sorted = branches.to_vec();
+    sorted.sort_by_key(|f| f.0);
+    let is_dense = match (sorted.first(), sorted.last()) {
+        (Some(first), Some(last)) => (last.0 as i64 - first.0 as i64 + 1) <= 2 * sorted.len() as i64,
+        _ => false
+    };
+    let mut tree:Vec<MCFunction> = Vec::new();
+    let commands = if is_dense {
+        dispatch_tree(score, &sorted, name, namespace, scope, &mut tree)
+    } else {
+        dispatch_flat(score, &sorted)
+    };
+    (commands, tree)
+}
+fn dispatch_flat(score:&Scoreboard, branches:&[(i32, String)]) -> Vec<String> {
+    branches
+        .iter()
+        .map(|f| format!("execute if {} run {}", score.pure_match_range(&f.0.to_string()), f.1))
+        .collect()
+}
+fn dispatch_tree(score:&Scoreboard, branches:&[(i32, String)], name:&str, namespace:&str, scope:&[String], tree:&mut Vec<MCFunction>) -> Vec<String> {
+    if branches.len() <= DISPATCH_TREE_THRESHOLD {
+        return dispatch_flat(score, branches)
+    }
+    let (left, right) = branches.split_at(branches.len() / 2);
+    let mut commands:Vec<String> = Vec::new();
+    for half in [left, right] {
+        let (lo, hi) = (half.first().unwrap().0, half.last().unwrap().0);
+        // '-' of negative keys are replaced because they look confusing in a name of function.
+        let node_name = format!("{}_{}_{}", name, lo, hi).replace('-', "m");
+        let inside = dispatch_tree(score, half, name, namespace, scope, tree).join("\n");
+        let node = MCFunction::generate(&node_name, inside, namespace, scope);
+        commands.push(
+            format!(
+                "execute if {} run {}",
+                score.pure_match_range(&format!("{}..{}", lo, hi)),
+                node.callment
+            )
+        );
+        tree.push(node);
+    }
+    commands
+}
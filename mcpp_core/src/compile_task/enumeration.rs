@@ -0,0 +1,47 @@
+use super::SentenceError;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A user-defined enumeration, declared like
+/// ```ignore
+/// enum State { Idle, Chase, Attack }
+/// ```
+/// A variable of an enumeration is stored as an int scoreboard,
+/// and each variants are numbered from 0 in declared order.
+///
+/// Variants are referenced like `State::Chase`.
+pub struct Enumeration {
+    pub name     : String,
+    pub variants : Vec<String>
+}
+
+impl Enumeration {
+    /// Parse the inside of an enum sentence.
+    pub fn parse(name:&str, inside:&[String]) -> Result<Enumeration, SentenceError> {
+        let mut variants:Vec<String> = Vec::new();
+        for variant in inside
+            .iter()
+            .flat_map(|f| f.split(','))
+            .map(|f| f.trim())
+            .filter(|f| !f.is_empty())
+        {
+            let is_valid = variant.chars().all(|c| c.is_alphanumeric() || c == '_');
+            if !is_valid || variants.iter().any(|f| f == variant) {
+                return Err(SentenceError::InvalidEnumerationVariant(variant.to_string()))
+            }
+            variants.push(variant.to_string());
+        }
+        Ok(
+            Enumeration {
+                name : name.to_string(),
+                variants : variants
+            }
+        )
+    }
+    /// Get the number corresponding to a variant.
+    pub fn get_index(&self, variant:&str) -> Option<i32> {
+        self.variants
+            .iter()
+            .position(|f| f == variant)
+            .map(|f| f as i32)
+    }
+}
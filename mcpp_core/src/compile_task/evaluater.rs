@@ -1,40 +1,203 @@
 use crate::Language;
 use crate::CURRENT_LANGUAGE;
 
+use super::scoreboard;
 use super::scoreboard::Types;
 // MC++ Crates
 use super::CompileTask;
 use super::Scoreboard;
 use super::scoreboard::Calcable;
+use super::scoreboard::Rounding;
+use super::scoreboard::float;
+use super::scoreboard::long;
 use super::MCFunction;
+use super::McVersion;
+use super::Enumeration;
+use super::call_graph;
+use super::mcfunction;
+use super::std_lib;
+use super::print;
+#[cfg(test)]
+use super::simulator::Simulator;
 
-use core::f32;
 // Outer Crates
 use std::fmt;
 use std::vec;
 use regex::Regex;
 
+#[test]
+fn recursion_test() {
+    let mut compiler = CompileTask::new();
+    let compiled = compiler.compile(
+        "fn count(n:int) -> int { r:int = 0; if n { r = count(n - 1) + 1 } return r }
+        fn main() { c:int = count(3) }",
+        "test"
+    ).unwrap();
+    let count = compiled.child_func.iter().find(|f| f.name == "count").unwrap();
+    let condition = count.child_func.first().unwrap();
+    println!("{}", condition.inside);
+    assert!(condition.inside.contains("data modify storage mcpp:call_stack frames append value {}"));
+    assert!(condition.inside.contains("execute store result storage mcpp:call_stack frames[-1].n int 1 run scoreboard players get #count.n MCPP.var"));
+    assert!(condition.inside.contains("execute store result score #r MCPP.var run data get storage mcpp:call_stack frames[-1].r"));
+    let main = compiled.child_func.iter().find(|f| f.name == "main").unwrap();
+    assert!(!main.inside.contains("mcpp:call_stack"));
+}
+#[test]
+fn call_in_expression_test() {
+    let mut compiler = CompileTask::new();
+    let compiled = compiler.compile(
+        "fn f(n:int) -> int { return n * 2 }
+        fn main() -> int { return 2 * 3 + f(4 * 5) - f(f(1) + 1) * 10 }",
+        "test"
+    ).unwrap();
+    let main = compiled.child_func.iter().find(|f| f.name == "main").unwrap();
+    println!("{}", main.inside);
+    let mut simulator = Simulator::new(&compiled);
+    simulator.call("test:main");
+    assert_eq!(simulator.score("#TEMP.RETURN_VALUE.main"), Some(2 * 3 + 4 * 5 * 2 - (2 + 1) * 2 * 10));
+}
+#[test]
+fn recursive_expression_test() {
+    let mut compiler = CompileTask::new();
+    let compiled = compiler.compile(
+        "fn count(n:int) -> int { if n { return n * 2 + count(n - 1) + n } return 0 }
+        fn main() -> int { return count(3) }",
+        "test"
+    ).unwrap();
+    let mut simulator = Simulator::new(&compiled);
+    simulator.call("test:main");
+    assert_eq!(simulator.score("#TEMP.RETURN_VALUE.main"), Some((3 + 2 + 1) * 3));
+}
+#[test]
+fn return_test() {
+    let source = "fn f(n:int) -> int { if n { return n * f(n - 1) } return 1 }
+        fn main() -> int { return f(3) }";
+    let mut compiler = CompileTask::new();
+    let compiled = compiler.compile(source, "test").unwrap();
+    let mut simulator = Simulator::new(&compiled);
+    simulator.call("test:main");
+    assert_eq!(simulator.score("#TEMP.RETURN_VALUE.main"), Some(6));
+
+    let mut old_compiler = CompileTask::new();
+    old_compiler.mc_version = McVersion::parse("1.20.2").unwrap();
+    let error = super::SentenceError::StatementAfterReturn(old_compiler.mc_version).to_string();
+    assert!(matches!(
+        old_compiler.compile(source, "test"),
+        Err(super::SentenceError::TypeErrors(e)) if e.iter().any(|f| f.contains(&error))
+    ));
+}
+#[test]
+fn bool_test() {
+    let mut compiler = CompileTask::new();
+    let compiled = compiler.compile("flag:bool = true; if flag { a:int = 1 }", "test").unwrap();
+    println!("{}", compiled.inside);
+    assert!(compiled.inside.contains("scoreboard players set #Calc.TEMP MCPP.var 1"));
+    assert!(compiled.inside.contains("execute if score #flag MCPP.var matches 1 run function test:"));
+
+    let mut task = CompileTask::new();
+    evaluate(&mut task, "flag:bool = false").unwrap();
+    assert!(matches!(evaluate(&mut task, "n:int = flag + 1"), Err(EvaluateError::ArithmeticOnBool(o)) if o == "+"));
+}
+#[test]
+fn cast_test() {
+    let mut task = CompileTask::new();
+    evaluate(&mut task, "f:float = 2.5").unwrap();
+    let casted = evaluate(&mut task, "i:int = f as int + round(f * 2)").unwrap().join("\n");
+    println!("{}", casted);
+    assert!(casted.contains("execute if score #Calc.AS0 MCPP.var matches ..-1 run scoreboard players add #Calc.AS0 MCPP.var 999"));
+    assert!(casted.contains("scoreboard players add #Calc.AS1 MCPP.var 500"));
+    assert_eq!(guess_type(&task, "f as int").unwrap(), Types::Int);
+    let flag = evaluate(&mut task, "b:bool = 3 as bool").unwrap().join("\n");
+    assert!(flag.contains("scoreboard players set #Calc.AS0 MCPP.var 1"));
+}
+#[test]
+fn precision_test() {
+    let mut task = CompileTask::new();
+    let declared = evaluate(&mut task, "a:fixed<1> = 2.5").unwrap().join("\n");
+    assert!(declared.contains("scoreboard players set #Calc.TEMP MCPP.var 25"));
+    let added = evaluate(&mut task, "b:float = a + 0.25").unwrap().join("\n");
+    println!("{}", added);
+    assert!(added.contains("scoreboard players operation #Calc.TEMP MCPP.var *= #CONSTANT.100 MCPP.var"));
+    assert!(added.contains("scoreboard players add #Calc.TEMP MCPP.var 250"));
+    assert_eq!(guess_type(&task, "a as fixed<4>").unwrap(), Types::Flt(4));
+    assert!(matches!(evaluate(&mut task, "c:fixed<10> = 1.0"), Err(EvaluateError::UnknownTypeAnnotation(_))));
+    // Literals are scaled without losing digits, and never clamped or wrapped.
+    let fine = evaluate(&mut task, "e:fixed<9> = 1.234567891").unwrap().join("\n");
+    assert!(fine.contains("scoreboard players set #Calc.TEMP MCPP.var 1234567891"));
+    assert!(matches!(evaluate(&mut task, "f:fixed<5> = 30000.0"), Err(EvaluateError::LiteralOutOfRange(_, _))));
+    assert!(matches!(evaluate(&mut task, "g:fixed<9> = e + 3"), Err(EvaluateError::LiteralOutOfRange(_, _))));
+
+    // Literals are typed and scaled by the precision of the project.
+    let mut fine_task = CompileTask::new();
+    fine_task.float_precision = 5;
+    assert_eq!(guess_type(&fine_task, "0.5 + 1").unwrap(), Types::Flt(5));
+    evaluate(&mut fine_task, "i:int = 2").unwrap();
+    let added = evaluate(&mut fine_task, "h:float = i + 0.5").unwrap().join("\n");
+    println!("{}", added);
+    assert!(added.contains("scoreboard players add #Calc.TEMP MCPP.var 50000"));
+}
 #[test]
 fn float_calc_test() {
     let mut task = CompileTask::new();
     println!("{}", evaluate(&mut task, "d:float = (0.03 * 0.2) + 0.05").unwrap().join("\n"));
+    evaluate(&mut task, "a:float = 300.5").unwrap();
+    let multiplied = evaluate(&mut task, "m:float = a * d").unwrap().join("\n");
+    println!("{}", multiplied);
+    assert!(multiplied.contains("scoreboard players operation #Calc.FIXED.ah MCPP.var = #Calc.TEMP MCPP.var"));
+    assert!(!multiplied.contains("#d MCPP.var *= #d MCPP.var"));
+    let divided = evaluate(&mut task, "q:float = a / d").unwrap().join("\n");
+    println!("{}", divided);
+    assert!(divided.contains("scoreboard players operation #Calc.FIXED.digit MCPP.var /= #Calc.FIXED.b MCPP.var"));
+
+    let compiled = CompileTask::new().compile("fn main() -> float { a:float = 5.5; return a % 2 }", "test").unwrap();
+    let mut simulator = Simulator::new(&compiled);
+    simulator.call("test:main");
+    assert_eq!(simulator.score("#TEMP.RETURN_VALUE.main"), Some(1500));
 }
 
-#[derive(Clone, Copy, Debug)]
+#[test]
+fn long_eval_test() {
+    let mut task = CompileTask::new();
+    assert_eq!(guess_type(&task, "5000000000 + 1").unwrap(), Types::Lng);
+    evaluate(&mut task, "n:int = 7").unwrap();
+    let multiplied = evaluate(&mut task, "l:long = 5000000000 * n - 1").unwrap().join("\n");
+    println!("{}", multiplied);
+    assert!(multiplied.contains("scoreboard players set #Calc.TEMP.hi MCPP.var 5"));
+    assert!(multiplied.contains("scoreboard players operation #Calc.TEMP.hi MCPP.var += #Calc.LONG.carry MCPP.var"));
+    assert!(evaluate(&mut task, "w:long = n").is_ok());
+    assert!(matches!(evaluate(&mut task, "m:int = l"), Err(EvaluateError::AssignOccuredBetweenUnsupportedTypes(_, _))));
+    assert!(evaluate(&mut task, "m:int = l as int").is_ok());
+    assert!(matches!(evaluate(&mut task, "q:long = l / 2"), Err(EvaluateError::OperationOccuredBetweenUnsupportedTypes(_, _))));
+    // The high half is a score, so a long is only about ±2.1e18.
+    let greatest = evaluate(&mut task, "g:long = 2147483647999999999").unwrap().join("\n");
+    assert!(greatest.contains("scoreboard players set #Calc.TEMP.hi MCPP.var 2147483647"));
+    assert!(matches!(evaluate(&mut task, "x:long = 3000000000000000000"), Err(EvaluateError::LiteralOutOfRange(_, Types::Lng))));
+    assert!(matches!(evaluate(&mut task, "y:long = 9000000000000000000"), Err(EvaluateError::LiteralOutOfRange(_, Types::Lng))));
+}
+#[derive(Clone, Debug)]
 pub enum FormulaToken<'a> {
     Int(i32),
-    Float(f32),
+    Long(i64),
+    /// A float literal and the precision of float of the compiler, because a literal has no precision itself.
+    Float(f64, u32),
+    Bool(bool),
     Scoreboard(&'a Scoreboard),
+    Variant(&'a Enumeration, i32),
     Operator(Operator),
-    MCFunction(&'a MCFunction)
+    Cast(Types, Rounding),
+    MCFunction(&'a MCFunction, &'a str)
 }
 impl FormulaToken<'_> {
     pub fn get_type(&self) -> Types {
         match self {
             FormulaToken::Int(_) => Types::Int,
-            FormulaToken::Float(_) => Types::Flt,
+            FormulaToken::Long(_) => Types::Lng,
+            FormulaToken::Float(_, p) => Types::Flt(*p),
+            FormulaToken::Bool(_) => Types::Bln,
             FormulaToken::Scoreboard(s) => s.data_type.clone(),
-            FormulaToken::MCFunction(f) => f.ret_container.data_type.clone(),
+            FormulaToken::Variant(e, _) => Types::Enum((*e).clone()),
+            FormulaToken::MCFunction(f, _) => f.ret_container.data_type.clone(),
+            FormulaToken::Cast(t, _) => t.clone(),
             _ => Types::Non
         }
     }
@@ -71,10 +234,14 @@ impl std::fmt::Display for FormulaToken<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             FormulaToken::Int(i) => write!(f, "{}", i.to_string()),
-            FormulaToken::Float(fl) => write!(f, "{}", fl.to_string()),
+            FormulaToken::Long(l) => write!(f, "{}", l),
+            FormulaToken::Float(fl, _) => write!(f, "{}", fl),
+            FormulaToken::Bool(b) => write!(f, "{}", b),
             FormulaToken::Operator(o) => write!(f, "{}", o),
             FormulaToken::Scoreboard(s) => write!(f,"{}", s),
-            FormulaToken::MCFunction(mcf) => write!(f, "{}(...)", mcf.name)
+            FormulaToken::Variant(e, i) => write!(f, "{}::{}", e.name, e.variants[*i as usize]),
+            FormulaToken::MCFunction(mcf, args) => write!(f, "{}({})", mcf.name, args),
+            FormulaToken::Cast(t, _) => write!(f, "as {}", t)
         }
     }
 }
@@ -82,6 +249,8 @@ impl PartialEq for FormulaToken<'_> {
     fn eq(&self, other: &Self) -> bool {
         return match (self, other) {
             (FormulaToken::Int(i), FormulaToken::Int(ii)) => i == ii,
+            (FormulaToken::Long(l), FormulaToken::Long(ll)) => l == ll,
+            (FormulaToken::Bool(b), FormulaToken::Bool(bb)) => b == bb,
             (FormulaToken::Operator(o), FormulaToken::Operator(oo)) => *o == *oo,
             (FormulaToken::Scoreboard(scr), FormulaToken::Scoreboard(scrscr)) => scr == scrscr,
             _ => panic!("Invalid comparement! lhs => {} | rhs => {}", self, other)
@@ -112,11 +281,23 @@ pub enum EvaluateError {
     OperationOccuredBetweenUnsupportedTypes(Types, Types),
     AssignOccuredBetweenUnsupportedTypes(Types, Types),
     ComparementOccuredBetweenUnsupportedTypes(Types, Types),
+    ArithmeticOnBool(String),
+    CastOccuredBetweenUnsupportedTypes(Types, Types),
+    VoidValueUsed,
     UndefinedFunctionCalled(String),
     UndefinedVariableReferenced(String),
+    UndefinedVariantReferenced(String),
     CouldntParseANumber(String),
+    LiteralOutOfRange(String, Types),
     UnknownOperatorGiven(String),
     UnknownTypeAnnotation(String),
+    WrongNumberOfArguments(String, usize, usize),
+    ReturnOutsideOfFunction,
+    InvalidRawCommand(String),
+    MacroArgumentOutsideOfMacro(String),
+    InvalidPrint(String),
+    UnknownMarkupTag(String),
+    UnprintableType(Types),
     UnbalancedBrackets,
     InvalidFormula
 }
@@ -133,21 +314,53 @@ impl fmt::Display for EvaluateError {
                 Self::ComparementOccuredBetweenUnsupportedTypes(left, right) => format!(
                     "Comparement between {} and {} is undefined operation.", left, right
                 ),
+                Self::ArithmeticOnBool(operator) => format!(
+                    "{} can't be applied onto bool. Use an if sentence to branch by a bool.", operator
+                ),
+                Self::CastOccuredBetweenUnsupportedTypes(value, onto) => format!(
+                    "Casting {} type value onto {} type is undefined operation.", value, onto
+                ),
+                Self::VoidValueUsed => "A function which returns nothing can't be used as a value.".to_string(),
                 Self::UndefinedFunctionCalled(func_name) => format!(
                     "An undefined function, {}(...) called.", func_name
                 ),
                 Self::UndefinedVariableReferenced(var_name) => format!(
                     "An undefined variable, {} referenced.", var_name
                 ),
+                Self::UndefinedVariantReferenced(variant) => format!(
+                    "An undefined variant of enum, {} referenced.", variant
+                ),
                 Self::CouldntParseANumber(invalid_num) => format!(
                     "{} couldn't be solved as number.", invalid_num
                 ),
+                Self::LiteralOutOfRange(literal, t) => format!(
+                    "{} doesn't fit in {}.", literal, t
+                ),
                 Self::UnknownOperatorGiven(invalid_oper) => format!(
                     "{} couldn't be solved as operator.", invalid_oper
                 ),
                 Self::UnknownTypeAnnotation(_type) => format!(
                     "The variable was annotated as {}, But {} is unknown", _type, _type
                 ),
+                Self::WrongNumberOfArguments(func_name, expected, given) => format!(
+                    "{}(...) takes {} argument(s), but {} given.", func_name, expected, given
+                ),
+                Self::ReturnOutsideOfFunction => "return can be used only inside of a function.".to_string(),
+                Self::InvalidRawCommand(raw) => format!(
+                    "run!{} is invalid. A command must be given as a string, like run!(\"say hi\").", raw
+                ),
+                Self::MacroArgumentOutsideOfMacro(command) => format!(
+                    "{} uses $(...), but it can be used only directly inside of a macro fn.", command
+                ),
+                Self::InvalidPrint(line) => format!(
+                    "{} is invalid. A text must be given as a string, like print(\"HP: {{hp}}\") or print_to(@s, \"HP: {{hp}}\").", line
+                ),
+                Self::UnknownMarkupTag(tag) => format!(
+                    "<{}> is unknown. A tag must be a color like <red> or <#ff8000>, or a style like <bold>.", tag
+                ),
+                Self::UnprintableType(t) => format!(
+                    "A value of {} type can't be printed.", t
+                ),
                 Self::UnbalancedBrackets => "Amount of right parenthese(s) and left parenthese(s) must be equal.".to_string(),
                 Self::InvalidFormula => "Invalid formula given.".to_string()
             },
@@ -161,41 +374,142 @@ impl fmt::Display for EvaluateError {
                 Self::ComparementOccuredBetweenUnsupportedTypes(left, right) => format!(
                     "{}型と{}型の比較は未定義操作です。", left, right
                 ),
+                Self::ArithmeticOnBool(operator) => format!(
+                    "bool型に{}は適用できません。boolによる分岐にはif文を使用してください。", operator
+                ),
+                Self::CastOccuredBetweenUnsupportedTypes(value, onto) => format!(
+                    "{}型の値を{}型にキャストする操作は未定義です。", value, onto
+                ),
+                Self::VoidValueUsed => "何も返さない関数は値として使用できません。".to_string(),
                 Self::UndefinedFunctionCalled(func_name) => format!(
                     "{}(...)は呼び出されましたが、宣言されていません。", func_name
                 ),
                 Self::UndefinedVariableReferenced(var_name) => format!(
                     "{}は参照されましたが、宣言されていません。", var_name
                 ),
+                Self::UndefinedVariantReferenced(variant) => format!(
+                    "列挙型の値{}は参照されましたが、宣言されていません。", variant
+                ),
                 Self::CouldntParseANumber(invalid_num) => format!(
                     "{}を数字として処理できませんでした。", invalid_num
                 ),
+                Self::LiteralOutOfRange(literal, t) => format!(
+                    "{}は{}に収まりません。", literal, t
+                ),
                 Self::UnknownOperatorGiven(invalid_oper) => format!(
                     "{}を算術記号として処理できませんでした。", invalid_oper
                 ),
                 Self::UnknownTypeAnnotation(_type) => format!(
                     "変数は{}として型注釈されていますが、{}は有効な型ではありません。", _type, _type
                 ),
+                Self::WrongNumberOfArguments(func_name, expected, given) => format!(
+                    "{}(...)は{}個の引数を取りますが、{}個与えられました。", func_name, expected, given
+                ),
+                Self::ReturnOutsideOfFunction => "returnは関数の中でのみ使用できます。".to_string(),
+                Self::InvalidRawCommand(raw) => format!(
+                    "run!{}は不正です。コマンドはrun!(\"say hi\")のように文字列で与えてください。", raw
+                ),
+                Self::MacroArgumentOutsideOfMacro(command) => format!(
+                    "{}は$(...)を使用していますが、これはmacro fnの直下でのみ使用できます。", command
+                ),
+                Self::InvalidPrint(line) => format!(
+                    "{}は不正です。テキストはprint(\"HP: {{hp}}\")やprint_to(@s, \"HP: {{hp}}\")のように文字列で与えてください。", line
+                ),
+                Self::UnknownMarkupTag(tag) => format!(
+                    "<{}>は不明なタグです。タグは<red>や<#ff8000>のような色か、<bold>のような装飾である必要があります。", tag
+                ),
+                Self::UnprintableType(t) => format!(
+                    "{}型の値は表示できません。", t
+                ),
                 Self::UnbalancedBrackets => "右かっこの数と左かっこの数が一致しません。".to_string(),
                 Self::InvalidFormula => "無効な式が与えられました。".to_string()
             }
         })
     }
 }
-pub fn guess_formula_type(formula:&Vec<FormulaToken<'_>>) -> Types {
-    formula
-        .iter()
-        .filter(
-            |f|
-            match f {
-                FormulaToken::Operator(_) => false,
-                _ => true
-            }
-        )
-        .collect::<Vec<&FormulaToken>>()
-        .get(0)
-        .unwrap()
-        .get_type()
+/// The function to infer the type of a reverse polish notation formula, checking types of every operations in it.
+/// 
+/// An operation between int and float is promoted onto float.
+/// 
+/// A function returns nothing is typed as none, and it can't be used as a value.
+pub fn infer_type(compiler:&CompileTask, formula:&[FormulaToken]) -> Result<Types, EvaluateError> {
+    let mut stack:Vec<Types> = Vec::new();
+    let pop = |stack:&mut Vec<Types>| match stack.pop() {
+        Some(Types::Non) => Err(EvaluateError::VoidValueUsed),
+        Some(t) => Ok(t),
+        None => Err(EvaluateError::InvalidFormula)
+    };
+    for token in formula {
+        match token {
+            FormulaToken::Operator(o) => {
+                let rhs = pop(&mut stack)?;
+                let lhs = pop(&mut stack)?;
+                stack.push(
+                    match (&lhs, &rhs, promote(&lhs, &rhs)) {
+                        (Types::Bln, _, _) | (_, Types::Bln, _) => return Err(EvaluateError::ArithmeticOnBool(o.to_string())),
+                        // A long emulates only the operations which can carry between its halves.
+                        (_, _, Some(Types::Lng)) if !matches!(o, Operator::Add | Operator::Rem | Operator::Mul) => return Err(
+                            EvaluateError::OperationOccuredBetweenUnsupportedTypes(lhs, rhs)
+                        ),
+                        (_, _, Some(t)) => t,
+                        _ => return Err(EvaluateError::OperationOccuredBetweenUnsupportedTypes(lhs, rhs))
+                    }
+                );
+            },
+            FormulaToken::Cast(t, _) => {
+                let value = pop(&mut stack)?;
+                match (&value, t) {
+                    (Types::Int | Types::Flt(_) | Types::Bln, Types::Int | Types::Flt(_) | Types::Bln)
+                        | (Types::Int | Types::Lng, Types::Int | Types::Lng)
+                        | (Types::Enum(_), Types::Int) => (),
+                    _ => return Err(EvaluateError::CastOccuredBetweenUnsupportedTypes(value, t.clone()))
+                }
+                stack.push(t.clone());
+            },
+            FormulaToken::MCFunction(f, args) => {
+                let given = split_arguments(args);
+                if given.len() != f.args.len() {
+                    return Err(EvaluateError::WrongNumberOfArguments(f.name.clone(), f.args.len(), given.len()))
+                }
+                for (param, arg) in f.args.iter().zip(given) {
+                    check_assignable(&guess_type(compiler, arg)?, &param.data_type)?;
+                }
+                stack.push(f.ret_container.data_type.clone());
+            },
+            _ => stack.push(token.get_type())
+        }
+    }
+    match stack.as_slice() {
+        [t] => Ok(t.clone()),
+        _ => Err(EvaluateError::InvalidFormula)
+    }
+}
+/// The pure function to get the type of a result of an arithmetic operation.
+/// 
+/// An operation with a float is promoted onto float, and the finer precision is kept.
+/// 
+/// An operation between int and long is promoted onto long, but long and float can't be mixed.
+fn promote(lhs:&Types, rhs:&Types) -> Option<Types> {
+    match (lhs, rhs) {
+        (Types::Int, Types::Int) => Some(Types::Int),
+        (Types::Flt(p), Types::Flt(q)) => Some(Types::Flt(*p.max(q))),
+        (Types::Flt(p), Types::Int) | (Types::Int, Types::Flt(p)) => Some(Types::Flt(*p)),
+        (Types::Int | Types::Lng, Types::Int | Types::Lng) => Some(Types::Lng),
+        _ => None
+    }
+}
+/// The pure function to check a value of a type can be assigned onto a variable of another type.
+/// 
+/// Int and float can be converted each other implicitly, and int can be widened onto long,
+/// but the others need the same type.
+pub fn check_assignable(value:&Types, onto:&Types) -> Result<(), EvaluateError> {
+    match (value, onto) {
+        (Types::Non, _) => Err(EvaluateError::VoidValueUsed),
+        (Types::Int | Types::Flt(_), Types::Int | Types::Flt(_)) => Ok(()),
+        (Types::Int, Types::Lng) => Ok(()),
+        (value, onto) if value == onto => Ok(()),
+        _ => Err(EvaluateError::AssignOccuredBetweenUnsupportedTypes(value.clone(), onto.clone()))
+    }
 }
 /// The function for convert &str type mathmetics operators onto the enum, Operator.
 fn to_operator(input:&str) -> Result<Operator, EvaluateError> {
@@ -224,27 +538,44 @@ fn to_a_formula_token<'a>(compiler:&'a CompileTask, input:&'a str) -> Result<For
             Ok(oper) => Ok(FormulaToken::Operator(oper)),
             Err(e) => Err(e)
         }
+    } else if _input == "true" || _input == "false" {
+        // Bool
+        Ok(FormulaToken::Bool(_input == "true"))
     } else if float_regex.is_match(_input) {
         // Float
-        match _input.parse::<f32>() {
-            Ok(f) => Ok(FormulaToken::Float(f)),
+        match _input.parse::<f64>() {
+            Ok(f) => Ok(FormulaToken::Float(f, compiler.float_precision)),
             Err(_) => Err(EvaluateError::CouldntParseANumber(_input.to_string()))
         }
     } else if int_regex.is_match(_input) {
         // Int
-        match _input.parse::<i32>() {
-            Ok(i) => Ok(FormulaToken::Int(i)),
-            Err(_) => Err(EvaluateError::CouldntParseANumber(_input.to_string()))
+        // A literal which doesn't fit in a score is a long.
+        match (_input.parse::<i32>(), _input.parse::<i64>()) {
+            (Ok(i), _) => Ok(FormulaToken::Int(i)),
+            (Err(_), Ok(l)) if (long::MIN..=long::MAX).contains(&l) => Ok(FormulaToken::Long(l)),
+            _ => Err(EvaluateError::LiteralOutOfRange(_input.to_string(), Types::Lng))
         }
-    } else if func_regex.is_match(input) {
+    } else if func_regex.is_match(_input) {
         // Function
         {
-            let func_name = input.split_once("(").unwrap().0.to_string();
-            match compiler.get_function(&func_name) {
-                Some(mf) => Ok(FormulaToken::MCFunction(mf)),
-                None => Err(EvaluateError::UndefinedFunctionCalled(func_name))
+            let (func_name, args) = _input.split_once("(").unwrap();
+            match compiler.get_function(&func_name.to_string()) {
+                Some(mf) => Ok(FormulaToken::MCFunction(
+                    std_lib::specialize(compiler, mf, &args[..args.len() - 1]),
+                    &args[..args.len() - 1]
+                )),
+                None => Err(EvaluateError::UndefinedFunctionCalled(func_name.to_string()))
             }
         }
+    } else if let Some((enum_name, variant)) = _input.split_once("::") {
+        // Variant of an enumeration
+        match compiler
+            .get_enumeration(enum_name)
+            .and_then(|e| e.get_index(variant).map(|i| (e, i)))
+        {
+            Some((e, i)) => Ok(FormulaToken::Variant(e, i)),
+            None => Err(EvaluateError::UndefinedVariantReferenced(_input.to_string()))
+        }
     } else {
         // Variable
         match compiler.get_variable(&_input.to_string()) {
@@ -253,20 +584,104 @@ fn to_a_formula_token<'a>(compiler:&'a CompileTask, input:&'a str) -> Result<For
         }
     }
 }
+/// The pure function to split a formula onto numbers, names, function calls and operators.
+/// 
+/// A name can contain dots to access fields of a structure, like `pos.x`,
+/// and colons to reference a variant of an enumeration, like `State::Idle`.
+/// 
+/// A function call is kept as one token with its arguments, like `add(a, (b + 1))`.
+fn split_formula(input:&str) -> Vec<&str> {
+    let chars:Vec<(usize, char)> = input.char_indices().collect();
+    let is_name = |c:char| c.is_alphanumeric() || c == '_' || c == '.' || c == ':';
+    let mut splitted:Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        i += 1;
+        if c.is_whitespace() {
+            continue;
+        }
+        if c.is_ascii_digit() || c == '.' {
+            while i < chars.len() && (chars[i].1.is_ascii_digit() || chars[i].1 == '.') { i += 1; }
+        } else if is_name(c) {
+            while i < chars.len() && is_name(chars[i].1) { i += 1; }
+            if i < chars.len() && chars[i].1 == '(' {
+                let mut depth = 0;
+                while i < chars.len() {
+                    match chars[i].1 {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        _ => ()
+                    }
+                    i += 1;
+                    if depth == 0 { break; }
+                }
+            }
+        }
+        let end = if i < chars.len() { chars[i].0 } else { input.len() };
+        splitted.push(&input[start..end]);
+    }
+    splitted
+}
+/// The pure function to split arguments of a function call by commas on the top level.
+pub fn split_arguments(args:&str) -> Vec<&str> {
+    let mut splitted:Vec<&str> = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                splitted.push(args[start..i].trim());
+                start = i + 1;
+            },
+            _ => ()
+        }
+    }
+    splitted.push(args[start..].trim());
+    splitted
+        .into_iter()
+        .filter(|f| !f.is_empty())
+        .collect()
+}
 /// The pure function to convert &str type formula onto vector of FormulaToken(s).
+/// 
+/// `expr as type` and built-in rounding functions, `round(expr)`, `floor(expr)` and `ceil(expr)`, are solved onto casts.
 fn to_formula_tokens<'a>(compiler:&'a CompileTask, input:&'a str) -> Result<Vec<FormulaToken<'a>>, EvaluateError> {
-    let re = Regex::new(r"(\d*[.]\d+|\d+|[a-zA-Z]+|[\+\-\*\(\)/])").unwrap();
-    let splitted:Vec<&'a str> = re
-        .find_iter(input)
-        .map(|m| m.as_str())
-        .collect();
-    let tokens = splitted
-        .iter()
-        .map(|s| to_a_formula_token(compiler,s))
-        .collect::<Vec<Result<FormulaToken<'a>, EvaluateError>>>();
+    let splitted:Vec<&'a str> = split_formula(input);
     let mut valid_tokens:Vec<FormulaToken<'a>> = Vec::new();
-    for token in tokens {
-        valid_tokens.push(token?)
+    let mut i = 0;
+    while i < splitted.len() {
+        let token = splitted[i].trim();
+        i += 1;
+        if token == "as" {
+            let mut annotation = splitted.get(i).ok_or(EvaluateError::InvalidFormula)?.trim().to_string();
+            i += 1;
+            // An annotation like fixed<4> is splitted onto some tokens.
+            if splitted.get(i).is_some_and(|f| f.trim() == "<") {
+                while let Some(part) = splitted.get(i) {
+                    annotation.push_str(part.trim());
+                    i += 1;
+                    if part.trim() == ">" { break; }
+                }
+            }
+            valid_tokens.push(FormulaToken::Cast(to_type(compiler, &annotation)?, Rounding::Trunc));
+            continue;
+        }
+        let rounding = token
+            .split_once("(")
+            .filter(|f| compiler.get_function(&f.0.to_string()).is_none())
+            .and_then(|f| Rounding::from_name(f.0).map(|r| (r, f.1)));
+        match rounding {
+            Some((rounding, args)) => {
+                valid_tokens.push(FormulaToken::Operator(Operator::LPt));
+                valid_tokens.append(&mut to_formula_tokens(compiler, &args[..args.len() - 1])?);
+                valid_tokens.push(FormulaToken::Operator(Operator::RPt));
+                valid_tokens.push(FormulaToken::Cast(Types::Int, rounding));
+            },
+            None => valid_tokens.push(to_a_formula_token(compiler, token)?)
+        }
     }
     Ok(valid_tokens)
 }
@@ -278,11 +693,17 @@ fn to_rpn<'a>(input:Vec<FormulaToken<'a>>) -> Result<Vec<FormulaToken<'a>>, Eval
     for token in input {
         match token {
             FormulaToken::Int(_)
-                | FormulaToken::Float(_)
+                | FormulaToken::Long(_)
+                | FormulaToken::Float(_, _)
+                | FormulaToken::Bool(_)
                 | FormulaToken::Scoreboard(_)
-                | FormulaToken::MCFunction(_) => {
+                | FormulaToken::Variant(_, _)
+                | FormulaToken::MCFunction(_, _) => {
                 output_queue.push(token);
             },
+            // A cast is a postfix operator which binds tighter than any other operators,
+            // so its operand is already on the output queue.
+            FormulaToken::Cast(_, _) => output_queue.push(token),
             FormulaToken::Operator(op) => match op {
                 Operator::LPt => {
                     operator_stack.push(op);
@@ -322,8 +743,8 @@ fn to_rpn<'a>(input:Vec<FormulaToken<'a>>) -> Result<Vec<FormulaToken<'a>>, Eval
 /// 
 /// The calcation commands will be kept in the first element of tuple,
 /// and a scoreboard that contains a result will be kept in the secound element of tuple.
-fn calc_rpn(formula:Vec<FormulaToken>, temp_score_data_type:Option<Types>) -> Result<(Vec<String>, Scoreboard), EvaluateError> {
-    let data_type = guess_formula_type(&formula);
+fn calc_rpn(compiler:&CompileTask, formula:Vec<FormulaToken>, temp_score_data_type:Option<Types>) -> Result<(Vec<String>, Scoreboard), EvaluateError> {
+    let data_type = infer_type(compiler, &formula)?;
     let temp = Scoreboard {
         name : "TEMP".to_string(),
         data_type : match temp_score_data_type {
@@ -332,60 +753,136 @@ fn calc_rpn(formula:Vec<FormulaToken>, temp_score_data_type:Option<Types>) -> Re
         },
         scope : vec!["Calc".to_string()]
     };
+    // Each depth of the stack has its own temporary scoreboard,
+    // so neither variables nor intermediate results are overwritten by an operation.
+    // It is typed by the value it keeps, so an intermediate result is never narrowed before the end.
+    let mut types:Vec<Types> = vec![temp.data_type.clone(), Types::Int];
+    types.extend((0..=float::MAX_PRECISION).map(Types::Flt));
+    types.push(Types::Lng);
+    types.extend(formula.iter().filter_map(|f| match f {
+        FormulaToken::MCFunction(f, _) => Some(f.ret_container.data_type.clone()),
+        _ => None
+    }));
+    let temps:Vec<Scoreboard> = (0..formula.len().max(1))
+        .flat_map(|i| types.iter().map(move |t| (i, t.clone())))
+        .map(|(i, t)| Scoreboard {
+            name : if i == 0 { temp.name.clone() } else { format!("{}{}", temp.name, i) },
+            data_type : t,
+            scope : temp.scope.clone()
+        })
+        .collect();
+    let temp_at = |depth:usize, data_type:&Types| temps
+        .iter()
+        .skip(depth * types.len())
+        .find(|f| f.data_type == *data_type)
+        .unwrap();
+    // Each cast has its own scoreboard as well, because its type can be different from the formula.
+    let casts:Vec<Scoreboard> = formula
+        .iter()
+        .filter_map(|f| match f { FormulaToken::Cast(t, _) => Some(t.clone()), _ => None })
+        .enumerate()
+        .map(|(i, t)| Scoreboard {
+            name : format!("AS{}", i),
+            data_type : t,
+            scope : temp.scope.clone()
+        })
+        .collect();
+    let mut casted = casts.iter();
     let mut responce:Vec<String> = Vec::new();
     let mut stack:Vec<Calcable> = Vec::new();
 
     for token in &formula {
         match token {
             FormulaToken::Int(i) => stack.push(Calcable::Int(*i)),
-            FormulaToken::Float(f) => stack.push(Calcable::Flt(*f)),
+            FormulaToken::Long(l) => stack.push(Calcable::Lng(*l)),
+            FormulaToken::Float(f, p) => stack.push(Calcable::Flt(*f, *p)),
+            FormulaToken::Bool(b) => stack.push(Calcable::Bln(*b)),
             FormulaToken::Scoreboard(s) => stack.push(Calcable::Scr(s)),
-            FormulaToken::MCFunction(f) => if &formula.len() <= &(1 as usize) {
-                responce.push(f.callment.clone()); 
-            } else {
-                stack.push(Calcable::Mcf(f))
+            FormulaToken::Variant(e, i) => stack.push(Calcable::Enm(e, *i)),
+            FormulaToken::MCFunction(f, args) => {
+                // Arguments and the function itself use the same temporary scoreboards,
+                // so intermediate results on the stack are kept on the call stack around the callment.
+                let live = stack
+                    .iter()
+                    .filter_map(|f| match *f {
+                        Calcable::Scr(s) if s.scope == temp.scope => Some((&s.name, s)),
+                        _ => None
+                    })
+                    .collect::<Vec<(&String, &Scoreboard)>>();
+                if !live.is_empty() {
+                    responce.append(&mut call_graph::push_frame(&live));
+                }
+                responce.append(&mut call_function(compiler, f, args)?);
+                // A returned value is copied at once, because calling the function again overwrites it.
+                let target = temp_at(stack.len(), &f.ret_container.data_type);
+                if f.ret_container.data_type != Types::Non {
+                    responce.push(target.assign(&Calcable::Mcf(f))?);
+                }
+                if !live.is_empty() {
+                    responce.append(&mut call_graph::pop_frame(&live));
+                }
+                stack.push(Calcable::Scr(target));
+            },
+            FormulaToken::Cast(_, rounding) => {
+                let value = stack.pop().ok_or(EvaluateError::InvalidFormula)?;
+                let target = casted.next().unwrap();
+                responce.push(target.cast(&value, *rounding)?);
+                stack.push(Calcable::Scr(target));
             },
             FormulaToken::Operator(o) => {
                 let rhs = stack.pop().unwrap();
                 let lhs = stack.pop().unwrap();
 
-                let target = match lhs {
-                    Calcable::Scr(s) => s,
-                    Calcable::Mcf(f) => { responce.push(f.callment.clone()); &f.ret_container },
-                    _ => { responce.push(temp.assign(&lhs)?); &temp }
-                };
-                responce.push(target.calc(format!("{}", &o).as_str(), &rhs)?);
+                let data_type = promote(&lhs.get_type(), &rhs.get_type()).unwrap_or(Types::Int);
+                let target = temp_at(stack.len(), &data_type);
+                if !matches!(lhs, Calcable::Scr(s) if s == target && s.data_type == target.data_type) {
+                    responce.push(target.assign(&lhs)?);
+                }
+                responce.push(target.calc_with(format!("{}", &o).as_str(), &rhs, compiler.overflow)?);
                 stack.push(Calcable::Scr(target));
             }
         }
     }
-    if formula.len() == 1 {
-        let first_element = match formula.first().unwrap() {
-            FormulaToken::Int(i) => Calcable::Int(*i),
-            FormulaToken::Float(f) => Calcable::Flt(*f),
-            FormulaToken::Scoreboard(s) => Calcable::Scr(s),
-            FormulaToken::MCFunction(f) => Calcable::Mcf(f),
-            _ => return Err(EvaluateError::InvalidFormula)
-        };
-        responce.push(temp.assign(&first_element)?);
+    match stack.pop() {
+        Some(Calcable::Scr(s)) if *s == temp && s.data_type == temp.data_type => (),
+        Some(result) => responce.push(temp.assign(&result)?),
+        None => return Err(EvaluateError::InvalidFormula)
     }
     Ok((responce, temp))
 }
-fn implicate_lhs(lhs:&str) -> Result<(String, Option<Types>), EvaluateError> {
+/// The function to solve a type annotation onto Types.
+/// 
+/// Structures and enumerations declared in given compiler can be used as well as primitive types.
+/// 
+/// `float` has the precision of given compiler, and `fixed<N>` is a float which has N digits under the decimal point.
+pub fn to_type(compiler:&CompileTask, annotation:&str) -> Result<Types, EvaluateError> {
+    let annotation = annotation.trim();
+    match annotation {
+        "int" => Ok(Types::Int),
+        "float" => Ok(Types::Flt(compiler.float_precision)),
+        "long" => Ok(Types::Lng),
+        "bool" => Ok(Types::Bln),
+        _ if annotation.starts_with("fixed<") => match annotation
+            .strip_prefix("fixed<")
+            .and_then(|f| f.strip_suffix(">"))
+            .and_then(|f| f.trim().parse::<u32>().ok())
+        {
+            Some(p) if p <= float::MAX_PRECISION => Ok(Types::Flt(p)),
+            _ => Err(EvaluateError::UnknownTypeAnnotation(annotation.to_string()))
+        },
+        _ => match (compiler.get_structure(annotation), compiler.get_enumeration(annotation)) {
+            (Some(s), _) => Ok(Types::Struct(s.clone())),
+            (None, Some(e)) => Ok(Types::Enum(e.clone())),
+            (None, None) => Err(EvaluateError::UnknownTypeAnnotation(annotation.to_string()))
+        }
+    }
+}
+fn implicate_lhs(compiler:&CompileTask, lhs:&str) -> Result<(String, Option<Types>), EvaluateError> {
     if lhs.contains(":") {
         let splitted = lhs.split_once(":").unwrap();
         Ok((
-            splitted.0.to_string(),
-            Some(
-                match splitted.1 {
-                    "int" => Types::Int,
-                    "float" => Types::Flt,
-                    "bool" => Types::Bln,
-                    _ => {
-                        return Err(EvaluateError::UnknownTypeAnnotation(splitted.1.to_string()))
-                    }
-                }
-            )
+            splitted.0.trim().to_string(),
+            Some(to_type(compiler, splitted.1)?)
         ))
     } else {
         Ok((
@@ -395,8 +892,76 @@ fn implicate_lhs(lhs:&str) -> Result<(String, Option<Types>), EvaluateError> {
     }
 }
 
+/// The function to evaluate arguments, then call a function.
+fn call_function(compiler:&CompileTask, function:&MCFunction, args:&str) -> Result<Vec<String>, EvaluateError> {
+    let given = split_arguments(args);
+    if given.len() != function.args.len() {
+        return Err(EvaluateError::WrongNumberOfArguments(function.name.clone(), function.args.len(), given.len()))
+    }
+    // Variables of the caller are kept on the call stack if the callee can call the caller back.
+    // It must be pushed before arguments are stored, because parameters can be variables of the caller.
+    let callee = compiler.call_graph.resolve(&compiler.sentence_path, &function.name);
+    let frame = match (&compiler.current_function, &callee) {
+        (Some(caller), Some(callee)) if compiler.call_graph.is_in_same_cycle(caller, callee) => Some(compiler.get_frame()),
+        _ => None
+    };
+    let mut result:Vec<String> = Vec::new();
+    if let Some(frame) = &frame {
+        result.append(&mut call_graph::push_frame(frame));
+    }
+    for (param, arg) in function.args.iter().zip(given) {
+        result.append(&mut eval_then_store(compiler, param, arg)?);
+        // Arguments of a function macro are passed through data storage.
+        if function.is_macro {
+            result.push(param.pure_store_to_storage(mcfunction::MACRO_STORAGE, &format!("{}.{}", function.name, param.name)));
+        }
+    }
+    result.push(function.callment.clone());
+    if let Some(frame) = &frame {
+        result.append(&mut call_graph::pop_frame(frame));
+    }
+    Ok(result)
+}
+/// The pure function to assign each fields of a structure onto another one.
+fn assign_fields(store_to:&Scoreboard, source:&Scoreboard) -> Result<Vec<String>, EvaluateError> {
+    if store_to.data_type != source.data_type {
+        return Err(EvaluateError::AssignOccuredBetweenUnsupportedTypes(source.data_type.clone(), store_to.data_type.clone()))
+    }
+    let mut result:Vec<String> = Vec::new();
+    for (field, source_field) in store_to.fields().iter().zip(source.fields()) {
+        match field.data_type {
+            Types::Struct(_) => result.append(&mut assign_fields(field, &source_field)?),
+            _ => result.push(field.assign(&Calcable::Scr(&source_field))?)
+        }
+    }
+    Ok(result)
+}
+/// The function to assign a structure, which is a variable or a returned value.
+fn eval_then_store_structure(compiler:&CompileTask, store_to:&Scoreboard, formula:&str) -> Result<Vec<String>, EvaluateError> {
+    let tokens = to_formula_tokens(compiler, formula)?;
+    let mut result:Vec<String> = vec![format!("# {} = {}", store_to.name, formula.trim())];
+    match tokens.as_slice() {
+        [FormulaToken::Scoreboard(s)] => result.append(&mut assign_fields(store_to, s)?),
+        [FormulaToken::MCFunction(f, args)] => {
+            result.append(&mut call_function(compiler, f, args)?);
+            result.append(&mut assign_fields(store_to, &f.ret_container)?);
+        },
+        _ => return Err(
+            EvaluateError::AssignOccuredBetweenUnsupportedTypes(infer_type(compiler, &to_rpn(tokens)?)?, store_to.data_type.clone())
+        )
+    }
+    Ok(result)
+}
+/// The function to guess the type of a formula without evaluating it.
+pub fn guess_type(compiler:&CompileTask, formula:&str) -> Result<Types, EvaluateError> {
+    infer_type(compiler, &to_rpn(to_formula_tokens(compiler, formula)?)?)
+}
 pub fn eval_then_store(compiler:&CompileTask, store_to:&Scoreboard, formula:&str) -> Result<Vec<String>, EvaluateError> {
+    if let Types::Struct(_) = store_to.data_type {
+        return eval_then_store_structure(compiler, store_to, formula)
+    }
     let calced = calc_rpn(
+        compiler,
         to_rpn(
             match to_formula_tokens(&compiler, formula) {
                 Ok(o) => o,
@@ -413,39 +978,98 @@ pub fn eval_then_store(compiler:&CompileTask, store_to:&Scoreboard, formula:&str
 
     Ok(result)
 }
+/// The impure function to solve the left hand side of a formula, declaring a new variable if needed.
+/// 
+/// The variable and the right hand side will be returned, or None if the formula has no left hand side.
+fn solve_lhs<'a>(compiler:&mut CompileTask, formula:&'a str) -> Result<Option<(Scoreboard, Option<&'a str>)>, EvaluateError> {
+    let (lhs, rhs) = match formula.split_once("=") {
+        Some(s) => (s.0.trim(), Some(s.1)),
+        None if formula.contains(":") => (formula.trim(), None),
+        None => return Ok(None)
+    };
+    let (lhs_name, data_type) = implicate_lhs(compiler, lhs)?;
+    if let (None, Some(declared)) = (&data_type, compiler.get_variable(&lhs_name)) {
+        return Ok(Some((declared.clone(), rhs)))
+    }
+    let declared = Scoreboard {
+        name : lhs_name.to_string(),
+        data_type : match data_type {
+            Some(s) => s,
+            None => match guess_type(compiler, rhs.unwrap_or_default())? {
+                Types::Non => return Err(EvaluateError::VoidValueUsed),
+                t => t
+            }
+        },
+        scope : compiler.scope.clone()
+    };
+    compiler.declare_variable(&lhs_name, declared.clone());
+    Ok(Some((declared, rhs)))
+}
+/// The pure function to compile a raw command, like `run!("say hi")`.
+/// 
+/// Inside of a macro fn, commands which contain $(...) are marked as macro lines.
+fn compile_raw_command(compiler:&CompileTask, raw:&str) -> Result<String, EvaluateError> {
+    let command = raw
+        .trim()
+        .strip_prefix("(")
+        .and_then(|f| f.strip_suffix(")"))
+        .map(|f| f.trim())
+        .and_then(|f| f.strip_prefix("\""))
+        .and_then(|f| f.strip_suffix("\""))
+        .ok_or(EvaluateError::InvalidRawCommand(raw.to_string()))?
+        .replace("\\\"", "\"")
+        .replace("\\\\", "\\");
+    match (command.contains("$("), compiler.in_macro) {
+        (true, true) => Ok(format!("${}", command)),
+        (true, false) => Err(EvaluateError::MacroArgumentOutsideOfMacro(command)),
+        (false, _) => Ok(command)
+    }
+}
 /// The impure function for evaluate a line.
 /// 
 /// It returns commands to apply the operations scribed on a formula.
 /// This function modify CompileTask because of definition of variables are processed in this function.
 /// It musn't called in this module.
+/// 
+/// # Declaration
+/// `name:type = formula` declares a new variable, and `name:type` declares it without initialisation.
+/// 
+/// Assigning onto a declared variable without a type annotation reuses the variable.
+/// 
+/// # Return
+/// `return formula` stores the result onto the return value of the function, then leaves the function.
+/// Blocks of the function leave by the exit flag, which their parents check after calling them.
+/// 
+/// Minecraft before 1.20.3 can't leave a function, so the result is only stored.
+/// 
+/// # Print
+/// `print("HP: {hp}")` shows a text to every players, and `print_to(@s, "...")` shows it to the target.
+/// Values in braces are shown as decimals, and tags like `<red>` or `<bold>` style the text.
 pub fn evaluate(compiler:&mut CompileTask, formula:&str) -> Result<Vec<String>, EvaluateError> {
-    let mut data_type:Option<Types> = None;
-    let mut lhs_name = String::new();
-    let rhs = match formula.split_once("=") {
-        Some(s) => {
-            let implicated = implicate_lhs(s.0.trim())?;
-            lhs_name = implicated.0;
-            data_type = implicated.1;
-            s.1
-        }
-        None => formula
-    };
-    if !lhs_name.is_empty() {
-        let lhs = Scoreboard {
-            name : lhs_name.to_string(),
-            data_type : if data_type.is_none() {
-                guess_formula_type(&to_rpn(to_formula_tokens(compiler, rhs)?)?)
-            } else {
-                data_type.unwrap()
-            },
-            scope : compiler.scope.clone()
+    if let Some(raw) = formula.trim().strip_prefix("run!") {
+        return Ok(vec![compile_raw_command(compiler, raw)?])
+    }
+    if let Some(printed) = print::parse_call(formula) {
+        let (target, text) = printed?;
+        return print::compile(compiler, &target, &text)
+    }
+    if let Some(returned) = formula.trim().strip_prefix("return ") {
+        let mut result = match &compiler.ret_container {
+            Some(ret) => eval_then_store(compiler, ret, returned)?,
+            None => return Err(EvaluateError::ReturnOutsideOfFunction)
         };
-        let var_name = lhs.name.clone();
-        compiler.local_variables.insert(var_name.clone(), lhs);
-        eval_then_store(compiler, &compiler.local_variables.get(&var_name).unwrap(), rhs)
-    } else {
-        Ok(
+        if compiler.mc_version >= McVersion::RETURN_FAIL {
+            result.push(scoreboard::get_exit_flag().pure_assign_value(1));
+            result.push("return 0".to_string());
+        }
+        return Ok(result)
+    }
+    match solve_lhs(compiler, formula)? {
+        Some((lhs, Some(rhs))) => eval_then_store(compiler, &lhs, rhs),
+        Some((_, None)) => Ok(Vec::new()),
+        None => Ok(
             calc_rpn(
+                compiler,
                 to_rpn(
                     match to_formula_tokens(&compiler, formula) {
                         Ok(o) => o,
@@ -457,11 +1081,45 @@ pub fn evaluate(compiler:&mut CompileTask, formula:&str) -> Result<Vec<String>,
         )
     }
 }
+/// The impure function to check types of a line without evaluating it.
+/// 
+/// Variables are declared as well as [evaluate], so following lines can be checked.
+pub fn check(compiler:&mut CompileTask, formula:&str) -> Result<(), EvaluateError> {
+    if let Some(raw) = formula.trim().strip_prefix("run!") {
+        return compile_raw_command(compiler, raw).map(|_| ())
+    }
+    if let Some(printed) = print::parse_call(formula) {
+        return print::check(compiler, &printed?.1)
+    }
+    if let Some(returned) = formula.trim().strip_prefix("return ") {
+        return match &compiler.ret_container {
+            Some(ret) => check_assignable(&guess_type(compiler, returned)?, &ret.data_type),
+            None => Err(EvaluateError::ReturnOutsideOfFunction)
+        }
+    }
+    match solve_lhs(compiler, formula)? {
+        Some((lhs, Some(rhs))) => check_assignable(&guess_type(compiler, rhs)?, &lhs.data_type),
+        Some((_, None)) => Ok(()),
+        None => guess_type(compiler, formula).map(|_| ())
+    }
+}
+/// The function to check a formula can be a condition of an if sentence, then return the type of it.
+pub fn check_condition(compiler:&CompileTask, condition:&str) -> Result<Types, EvaluateError> {
+    match guess_type(compiler, condition)? {
+        Types::Non => Err(EvaluateError::VoidValueUsed),
+        t @ (Types::Int | Types::Flt(_) | Types::Bln) => Ok(t),
+        t => Err(EvaluateError::ComparementOccuredBetweenUnsupportedTypes(t, Types::Bln))
+    }
+}
 pub fn add_execution_condition(compiler:&CompileTask, temp_restraint_var_name:&str, command:&str, condition:&str) -> Result<String, EvaluateError> {
     let rpn_formula = to_rpn(to_formula_tokens(compiler, condition)?)?;
+    // A bool variable is a condition as it is, so it doesn't need to be copied.
+    if let [FormulaToken::Scoreboard(s)] = rpn_formula.as_slice() && s.data_type == Types::Bln {
+        return Ok(format!("execute if {} run {}", s.pure_compare_value("==", 1)?, command))
+    }
     let store_to  = Scoreboard {
         name : temp_restraint_var_name.to_string(),
-        data_type : guess_formula_type(&rpn_formula),
+        data_type : check_condition(compiler, condition)?,
         scope : vec!["TEMP".to_string(), "EVAL_CONDITION".to_string()]
     };
     let evaluation = eval_then_store(compiler, &store_to, condition)?;
@@ -0,0 +1,124 @@
+//! Functions called by events.
+//!
+//! `on player_hurt_entity fn name() {...}` declares a function called by an event, backed by a generated advancement.
+
+use super::SentenceError;
+
+#[test]
+fn event_test() {
+    let (event, rest) = Event::split_off("on item_used(carrot_on_a_stick) fn use_stick").unwrap();
+    assert_eq!(rest, "fn use_stick");
+    let event = event.unwrap();
+    println!("{}", event.to_json("test:use_stick"));
+    assert!(event.to_json("test:use_stick").contains("\"items\":[\"minecraft:carrot_on_a_stick\"]"));
+    assert!(event.revokes());
+
+    let (event, _) = Event::split_off("on advancement(\"minecraft:story/mine_stone\") fn mined").unwrap();
+    let event = event.unwrap();
+    assert!(event.to_json("test:mined").contains("\"advancements\":{\"minecraft:story/mine_stone\":true}"));
+    assert!(!event.revokes());
+
+    assert!(Event::split_off("fn plain").unwrap().0.is_none());
+    assert!(Event::split_off("on jumped fn jump").is_err());
+    assert!(Event::split_off("on player_killed").is_err());
+
+    let mut compiler = super::CompileTask::new();
+    let compiled = compiler.compile("on player_hurt_entity fn hit() { print(\"hit!\") }", "test").unwrap();
+    let hit = compiled.child_func.iter().find(|f| f.name == "hit").unwrap();
+    println!("{}", hit.inside);
+    assert!(hit.inside.starts_with("advancement revoke @s only test:hit"));
+    assert_eq!(hit.event, Some(Event::Trigger("player_hurt_entity".to_string(), None)));
+    assert!(super::CompileTask::new().compile("on player_died fn died(n:int) { }", "test").is_err());
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// An event which calls a function, declared like `on player_hurt_entity fn name() {...}`.
+///
+/// Each event is backed by an advancement whose reward is the function.
+pub enum Event {
+    /// Fired once when a player completes the advancement, like `on advancement("minecraft:story/mine_stone")`.
+    Advancement(String),
+    /// Fired each time the trigger of an advancement is met, with conditions of it as JSON.
+    Trigger(String, Option<String>)
+}
+
+/// The pure function to complete an id without a namespace onto minecraft:id.
+fn with_namespace(id:&str) -> String {
+    if id.contains(':') { id.to_string() } else { format!("minecraft:{}", id) }
+}
+
+impl Event {
+    /// Split `on <event>` off the head of a sentence, then return the event and the rest of the specifier.
+    ///
+    /// The rest must declare a function, because the event needs a function to call.
+    pub fn split_off(specifier:&str) -> Result<(Option<Event>, &str), SentenceError> {
+        let trimmed = specifier.trim();
+        let Some(after_on) = trimmed.strip_prefix("on ") else {
+            return Ok((None, trimmed))
+        };
+        let fn_at = after_on
+            .match_indices("fn")
+            .map(|(i, _)| i)
+            .find(|i| {
+                let before = after_on[..*i].chars().last();
+                let after = after_on[i + 2..].chars().next();
+                before.is_some_and(|c| c.is_whitespace()) && after.is_none_or(|c| c.is_whitespace())
+            })
+            .ok_or(SentenceError::InvalidEventHandler(trimmed.to_string()))?;
+        Ok((Some(Event::parse(after_on[..fn_at].trim())?), after_on[fn_at..].trim()))
+    }
+    /// Parse an event like `advancement("minecraft:story/mine_stone")` or `item_used(carrot_on_a_stick)`.
+    fn parse(raw:&str) -> Result<Event, SentenceError> {
+        let (name, argument) = match raw.split_once('(') {
+            Some((name, argument)) => match argument.trim_end().strip_suffix(')') {
+                Some(a) => (name.trim(), Some(a.trim().trim_matches('"'))),
+                None => return Err(SentenceError::UnknownEvent(raw.to_string()))
+            },
+            None => (raw, None)
+        };
+        let event = match (name, argument) {
+            ("advancement", Some(id)) => Event::Advancement(with_namespace(id)),
+            ("player_hurt_entity", None) => Event::Trigger("player_hurt_entity".to_string(), None),
+            ("entity_hurt_player", None) => Event::Trigger("entity_hurt_player".to_string(), None),
+            ("player_killed", None) => Event::Trigger("player_killed_entity".to_string(), None),
+            ("player_killed", Some(entity)) => Event::Trigger(
+                "player_killed_entity".to_string(),
+                Some(format!("{{\"entity\":{{\"type\":\"{}\"}}}}", with_namespace(entity)))
+            ),
+            ("player_died", None) => Event::Trigger("entity_killed_player".to_string(), None),
+            ("item_used", Some(item)) => Event::Trigger(
+                "using_item".to_string(),
+                Some(format!("{{\"item\":{{\"items\":[\"{}\"]}}}}", with_namespace(item)))
+            ),
+            ("item_consumed", Some(item)) => Event::Trigger(
+                "consume_item".to_string(),
+                Some(format!("{{\"item\":{{\"items\":[\"{}\"]}}}}", with_namespace(item)))
+            ),
+            _ => return Err(SentenceError::UnknownEvent(raw.to_string()))
+        };
+        Ok(event)
+    }
+    /// Whether the handler revokes the advancement, so the event can fire again.
+    ///
+    /// The advancement of `on advancement(...)` is met every tick after the player completed it,
+    /// so it is never revoked and fires only once.
+    pub fn revokes(&self) -> bool {
+        matches!(self, Event::Trigger(..))
+    }
+    /// The pure function to get the JSON of the advancement, which calls given function as its reward.
+    pub fn to_json(&self, function_id:&str) -> String {
+        let (trigger, conditions) = match self {
+            Event::Advancement(id) => (
+                "tick".to_string(),
+                Some(format!("{{\"player\":{{\"type_specific\":{{\"type\":\"player\",\"advancements\":{{\"{}\":true}}}}}}}}", id))
+            ),
+            Event::Trigger(trigger, conditions) => (trigger.clone(), conditions.clone())
+        };
+        format!(
+            "{{\n    \"criteria\":{{\n        \"event\":{{\n            \"trigger\":\"minecraft:{}\"{}\n        }}\n    }},\n    \"rewards\":{{\n        \"function\":\"{}\"\n    }}\n}}",
+            trigger,
+            conditions.map(|f| format!(",\n            \"conditions\":{}", f)).unwrap_or_default(),
+            function_id
+        )
+    }
+}
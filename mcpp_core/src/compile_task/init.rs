@@ -0,0 +1,80 @@
+//! Initialization of a pack.
+//!
+//! Objectives and constants like #CONSTANT.100 used in the pack are made by namespace:mcpp_init, which is run from minecraft:load.
+
+use std::collections::BTreeSet;
+
+use regex::Regex;
+
+use super::{MCFunction, Scoreboard};
+
+/// The name of the function which initializes scoreboards on load, put in the namespace of each pack.
+pub const INIT_FUNCTION:&str = "mcpp_init";
+/// The function tag run when the world is loaded or reloaded.
+pub const LOAD_TAG:&str = "minecraft:load";
+
+#[test]
+fn init_test() {
+    let compiled = super::CompileTask::new().compile("a:float = 1.5; b:float = a * a; c:float = b * a", "test").unwrap();
+    let init = compiled.child_func.iter().find(|f| f.name == INIT_FUNCTION).unwrap();
+    println!("{}", init.inside);
+    assert_eq!(init.inside.matches("scoreboard objectives add MCPP.var dummy").count(), 1);
+    assert_eq!(init.inside.matches("scoreboard players set #CONSTANT.1000 MCPP.var 1000\n").count(), 1);
+    // Constants are only set by test:mcpp_init.
+    assert!(compiled.inside.contains("#CONSTANT.1000 "));
+    assert!(!compiled.inside.contains("scoreboard players set #CONSTANT"));
+    let files = compiled.collect_files(super::McVersion::parse("1.21.4").unwrap());
+    assert!(files.contains_key("data/test/function/mcpp_init.mcfunction"));
+    assert!(files["data/minecraft/tags/function/load.json"].contains("\"test:mcpp_init\""));
+
+    // Constants come from the commands of the pack, so the same compiler never carries them to the next pack.
+    let mut compiler = super::CompileTask::new();
+    let first = compiler.compile("a:int = 3; b:int = a * 7", "test").unwrap();
+    let second = compiler.compile("a:int = 1", "test").unwrap();
+    let get_init = |f:&MCFunction| f.child_func.iter().find(|f| f.name == INIT_FUNCTION).unwrap().inside.clone();
+    assert!(get_init(&first).contains("#CONSTANT.7 "));
+    assert!(!get_init(&second).contains("#CONSTANT.7 "));
+
+    // Constants are ready before any function runs.
+    let compiled = super::CompileTask::new().compile("fn main() -> int { a:int = 7; return a * 3 }", "test").unwrap();
+    let mut simulator = super::simulator::Simulator::new(&compiled);
+    simulator.call("test:main");
+    assert_eq!(simulator.score("#TEMP.RETURN_VALUE.main"), Some(21));
+}
+
+/// Collect objectives and constants used in commands of given function and its children.
+fn collect_scores(function:&MCFunction, score_regex:&Regex, objectives:&mut BTreeSet<String>, constants:&mut BTreeSet<i32>) {
+    // Comments are skipped, so words after # in them are never taken as scores.
+    for line in function.inside.lines().filter(|f| !f.trim_start().starts_with('#')) {
+        for captures in score_regex.captures_iter(line) {
+            if let Some(value) = captures[1].strip_prefix("CONSTANT.").and_then(|f| f.parse::<i32>().ok()) {
+                constants.insert(value);
+            }
+            objectives.insert(captures[2].to_string());
+        }
+    }
+    for f in &function.child_func {
+        collect_scores(f, score_regex, objectives, constants);
+    }
+}
+
+/// Generate namespace:mcpp_init, which adds every objective used in given function and its children,
+/// then sets every constant like #CONSTANT.100 used in them.
+///
+/// It is added to minecraft:load, so scoreboards are ready on a fresh world.
+/// Each pack has its own, so packs compiled by MCPP can be loaded together.
+pub fn generate_init(root:&MCFunction, namespace:&str) -> MCFunction {
+    // Fake players like #name objective, which are the only scores generated by the compiler.
+    let score_regex = Regex::new(r"#([\w.\-]+) ([A-Za-z_][\w.\-+]*)").unwrap();
+    let mut objectives:BTreeSet<String> = BTreeSet::new();
+    let mut constants:BTreeSet<i32> = BTreeSet::new();
+    collect_scores(root, &score_regex, &mut objectives, &mut constants);
+
+    let mut inside = vec!["# Objectives".to_string()];
+    inside.extend(objectives.iter().map(|f| format!("scoreboard objectives add {} dummy", f)));
+    inside.push("\n# Constants".to_string());
+    inside.extend(constants.iter().map(|f| Scoreboard::from(*f).pure_assign_value(*f)));
+    let mut init = MCFunction::generate(INIT_FUNCTION, inside.join("\n") + "\n", namespace, &[]);
+    init.tags.push(LOAD_TAG.to_string());
+    init
+}
@@ -0,0 +1,171 @@
+use super::{dispatch, evaluater, CompileTask, Line, Scoreboard, Sentence, SentenceError};
+use super::scoreboard::Types;
+
+#[test]
+fn match_test() {
+    let mut compiler = CompileTask::new();
+    let compiled = compiler.compile(
+        "enum State { Idle, Chase, Attack }
+        state:State = State::Chase;
+        match state {
+            State::Idle => { a = 0 },
+            State::Chase | State::Attack => { a = 1 }
+        }",
+        "test"
+    ).unwrap();
+    println!("\n{}", compiled.inside);
+    assert!(compiled.inside.contains("scoreboard players set #Calc.TEMP MCPP.var 1"));
+    assert!(compiled.inside.contains("matches 0 run function test:"));
+    assert!(compiled.inside.contains("matches 2 run function test:"));
+
+    let mut compiler = CompileTask::new();
+    let not_exhaustive = compiler.compile(
+        "enum State { Idle, Chase, Attack }
+        state:State = State::Idle;
+        match state { State::Idle => { a = 0 } }",
+        "test"
+    );
+    assert!(matches!(not_exhaustive, Err(SentenceError::NonExhaustiveMatch(m)) if m == vec!["State::Chase", "State::Attack"]));
+}
+
+/// A pattern of an arm of match.
+enum Pattern {
+    Value(i32),
+    Wildcard
+}
+
+impl Sentence {
+    /// The function to solve a pattern, like `State::Idle`, `3` or `_`.
+    fn to_pattern(data_type:&Types, raw:&str) -> Result<Pattern, SentenceError> {
+        if raw == "_" {
+            return Ok(Pattern::Wildcard)
+        }
+        match data_type {
+            Types::Enum(e) => match raw.split_once("::") {
+                Some((enum_name, variant)) if enum_name == e.name => match e.get_index(variant) {
+                    Some(i) => Ok(Pattern::Value(i)),
+                    None => Err(SentenceError::InvalidMatchArm(raw.to_string()))
+                },
+                _ => Err(SentenceError::InvalidMatchArm(raw.to_string()))
+            },
+            _ => match raw.parse::<i32>() {
+                Ok(i) => Ok(Pattern::Value(i)),
+                Err(_) => Err(SentenceError::InvalidMatchArm(raw.to_string()))
+            }
+        }
+    }
+    /// Compile a match sentence, like
+    /// ```ignore
+    /// match state {
+    ///     State::Idle => {...},
+    ///     State::Chase | State::Attack => {...},
+    ///     _ => {...}
+    /// }
+    /// ```
+    /// The matched value is copied onto a temporary scoreboard at first,
+    /// so arms which modify the original value never run another arm.
+    ///
+    /// Every variants of an enum, or _ for an int, must be covered.
+    pub(super) fn compile_match(&self, compiler:&mut CompileTask, namespace:&str) -> Result<String, SentenceError> {
+        let formula = self.specifiers[1..].join(" ");
+        let matched = Scoreboard {
+            name : self.name.clone(),
+            data_type : match evaluater::guess_type(compiler, &formula) {
+                Ok(o) => o,
+                Err(e) => return Err(SentenceError::InvalidFormula(e))
+            },
+            scope : vec!["TEMP".to_string(), "MATCH".to_string()]
+        };
+        if !matches!(matched.data_type, Types::Int | Types::Enum(_)) {
+            return Err(SentenceError::UnmatchableType(matched.data_type.clone()))
+        }
+
+        // Solve patterns of each arms
+        let mut keys:Vec<i32> = Vec::new();
+        let mut arms:Vec<(&Sentence, Vec<i32>)> = Vec::new();
+        let mut wildcard:Option<&Sentence> = None;
+        for line in &self.parsed_lines {
+            let arm = match line {
+                Line::Sentence(s) => s,
+                Line::Formula(f) | Line::Comment(f) => return Err(SentenceError::InvalidMatchArm(f.clone()))
+            };
+            let raw_patterns = match arm.specifiers.join(" ").strip_suffix("=>") {
+                Some(s) => s.to_string(),
+                None => return Err(SentenceError::InvalidMatchArm(arm.specifiers.join(" ")))
+            };
+            let mut arm_keys:Vec<i32> = Vec::new();
+            for raw in raw_patterns.split('|').map(|f| f.trim()) {
+                match Self::to_pattern(&matched.data_type, raw)? {
+                    Pattern::Value(i) => {
+                        if keys.contains(&i) {
+                            return Err(SentenceError::DuplicatedMatchArm(raw.to_string()))
+                        }
+                        keys.push(i);
+                        arm_keys.push(i);
+                    },
+                    Pattern::Wildcard => {
+                        if wildcard.is_some() {
+                            return Err(SentenceError::DuplicatedMatchArm(raw.to_string()))
+                        }
+                        wildcard = Some(arm);
+                    }
+                }
+            }
+            if !arm_keys.is_empty() {
+                arms.push((arm, arm_keys));
+            }
+        }
+
+        // Check exhaustiveness
+        if wildcard.is_none() {
+            let missing = match &matched.data_type {
+                Types::Enum(e) => e.variants
+                    .iter()
+                    .enumerate()
+                    .filter(|f| !keys.contains(&(f.0 as i32)))
+                    .map(|f| format!("{}::{}", e.name, f.1))
+                    .collect::<Vec<String>>(),
+                _ => vec!["_".to_string()]
+            };
+            if !missing.is_empty() {
+                return Err(SentenceError::NonExhaustiveMatch(missing))
+            }
+        }
+
+        // Compile arms, then dispatch
+        let mut res = match evaluater::eval_then_store(compiler, &matched, &formula) {
+            Ok(o) => o,
+            Err(e) => return Err(SentenceError::InvalidFormula(e))
+        };
+        let mut branches:Vec<(i32, String)> = Vec::new();
+        for (arm, arm_keys) in &arms {
+            let compiled = compiler.spawn_slave().compile_sentence(arm, namespace)?;
+            for key in arm_keys {
+                branches.push((*key, compiled.callment.clone()));
+            }
+            compiler.local_functions.insert(compiled.name.clone(), compiled);
+        }
+        let (dispatched, tree) = dispatch::dispatch(&matched, &branches, &self.name, namespace, &compiler.scope);
+        res.extend(dispatched);
+        for node in tree {
+            compiler.local_functions.insert(node.name.clone(), node);
+        }
+        if let Some(arm) = wildcard {
+            let compiled = compiler.spawn_slave().compile_sentence(arm, namespace)?;
+            keys.sort();
+            res.push(
+                format!(
+                    "execute {}run {}",
+                    dispatch::to_ranges(&keys)
+                        .iter()
+                        .map(|f| format!("unless {} ", matched.pure_match_range(f)))
+                        .collect::<String>(),
+                    compiled.callment
+                )
+            );
+            compiler.local_functions.insert(compiled.name.clone(), compiled);
+        }
+        res.push(matched.free());
+        Ok(res.join("\n"))
+    }
+}
@@ -1,5 +1,14 @@
-use super::Scoreboard;
-use std::{fs, io::Write};
+//! Functions of Minecraft generated by the compiler, and files of datapacks made of them.
+//!
+//! # Macros
+//! Functions declared by `macro fn` are function macros of Minecraft.
+//!
+//! Arguments are stored onto data storage at the call site, and `$(name)` in `run!("...")` is replaced by them.
+//!
+//! They need Minecraft 1.20.2 or later, which is given as mc_version.
+
+use super::{Event, McVersion, Scoreboard};
+use std::{collections::BTreeMap, fs, io::Write};
 
 #[derive(Clone, Debug)]
 pub struct MCFunction {
@@ -11,7 +20,12 @@ pub struct MCFunction {
     pub child_func : Vec<MCFunction>,
     pub scope      : Vec<String>,
 
-    pub ret_container : Scoreboard
+    pub args          : Vec<Scoreboard>,
+    pub ret_container : Scoreboard,
+    pub is_macro      : bool,
+    pub event         : Option<Event>,
+    /// Function tags which the function is added to, like minecraft:tick.
+    pub tags          : Vec<String>
 }
 impl std::fmt::Display for MCFunction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -19,14 +33,34 @@ impl std::fmt::Display for MCFunction {
     }
 }
 
-fn generate_pack_mcmeta(inputs:Vec<(&str, &str)>) -> String {
+/// The pure function to get pack.mcmeta from its sections like pack, whose values are given as JSON.
+pub(super) fn generate_pack_mcmeta(sections:Vec<(&str, Vec<(&str, String)>)>) -> String {
     format!(
-        "{{\n   \"pack\":{{\n{}\n   }}\n}}",
-        inputs
+        "{{\n{}\n}}",
+        sections
             .iter()
-            .map(|f| format!("      \"{}\":\"{}\",", f.0, f.1))
+            .map(|(name, inputs)| format!(
+                "   \"{}\":{{\n{}\n   }}",
+                name,
+                inputs
+                    .iter()
+                    .map(|f| format!("      \"{}\":{}", f.0, f.1))
+                    .collect::<Vec<String>>()
+                    .join(",\n")
+            ))
             .collect::<Vec<String>>()
-            .join("\n")
+            .join(",\n")
+    )
+}
+
+fn generate_function_tag(values:&[String]) -> String {
+    format!(
+        "{{\n   \"values\":[\n{}\n   ]\n}}",
+        values
+            .iter()
+            .map(|f| format!("      \"{}\"", f))
+            .collect::<Vec<String>>()
+            .join(",\n")
     )
 }
 
@@ -36,43 +70,136 @@ pub fn make_a_file(path:&str, file_name:&str, content:&str) -> std::io::Result<(
     Ok(())
 }
 
+/// Write files onto given folder by their paths from it, making folders on the way.
+pub(super) fn write_files(root:&str, files:&BTreeMap<String, String>) -> std::io::Result<()> {
+    for (path, content) in files {
+        let (folder, file_name) = path.rsplit_once('/').unwrap_or(("", path));
+        let folder = format!("{}/{}", root, folder);
+        fs::create_dir_all(&folder)?;
+        make_a_file(&folder, file_name, content)?;
+    }
+    Ok(())
+}
+/// Make an empty folder of a pack, then return the path of it.
+/// 
+/// The old folder is removed if it exists.
+pub(super) fn prepare_pack_root(root_path:&str, pack_name:&str) -> std::io::Result<String> {
+    let pack_root = format!("{}/{}", root_path, pack_name);
+    if fs::exists(&pack_root)? {
+        fs::remove_dir_all(&pack_root)?;
+    }
+    fs::create_dir_all(&pack_root)?;
+    Ok(pack_root)
+}
+
+/// The data storage where arguments of function macros are stored, like `mcpp:macro <function>.<param>`.
+pub const MACRO_STORAGE:&str = "mcpp:macro";
+
+/// The pure function to get a command to report an error of MC++ to every players in runtime.
+pub fn pure_runtime_error(message:&str) -> String {
+    format!(
+        "tellraw @a {{\"text\":\"MC++ runtime error: {}\",\"color\":\"red\"}}",
+        message.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+/// The pure function to get the id of a function or an advancement, like namespace:scope/name.
+pub fn get_id(namespace:&str, scope:&[String], name:&str) -> String {
+    format!(
+        "{}:{}{}{}",
+        namespace,
+        scope.join("/"),
+        if scope.is_empty() {""} else {"/"},
+        name
+    )
+}
+/// The pure function to get a command to call a function.
+pub fn get_callment(namespace:&str, scope:&[String], name:&str) -> String {
+    format!("function {}", get_id(namespace, scope, name))
+}
+
+/// The pure function to get a command to call a function macro with its arguments.
+pub fn get_macro_callment(namespace:&str, scope:&[String], name:&str) -> String {
+    format!("{} with storage {} {}", get_callment(namespace, scope, name), MACRO_STORAGE, name)
+}
+
 impl MCFunction {
-    fn save(&self, funcs_path:&str) -> std::io::Result<()> {
-        let path = format!("{}/{}", funcs_path, self.scope.join("/"));
-        make_a_file(&path, &format!("{}.mcfunction", self.name), &self.inside)?;
+    /// Make a function which is generated by the compiler, not compiled from a sentence.
+    pub fn generate(name:&str, inside:String, namespace:&str, scope:&[String]) -> MCFunction {
+        MCFunction {
+            name : name.to_string(),
+            inside : inside,
+            callment : get_callment(namespace, scope, name),
+            namespace : namespace.to_string(),
+            child_func : Vec::new(),
+            scope : scope.to_vec(),
+            args : Vec::new(),
+            ret_container : Scoreboard {
+                name  : format!("TEMP.RETURN_VALUE.{}", name),
+                data_type : super::scoreboard::Types::Non,
+                scope : Vec::new()
+            },
+            is_macro : false,
+            event : None,
+            tags : Vec::new()
+        }
+    }
+    /// Collect files of this function and its children, and advancements of event handlers in them.
+    fn collect_functions(&self, mc_version:McVersion, files:&mut BTreeMap<String, String>) {
+        let path = |kind:&str, extension:&str| format!(
+            "data/{}/{}/{}{}{}.{}",
+            self.namespace,
+            mc_version.folder(kind),
+            self.scope.join("/"),
+            if self.scope.is_empty() {""} else {"/"},
+            self.name,
+            extension
+        );
+        files.insert(path("function", "mcfunction"), self.inside.clone());
+        if let Some(event) = &self.event {
+            files.insert(path("advancement", "json"), event.to_json(&get_id(&self.namespace, &self.scope, &self.name)));
+        }
+        for f in &self.child_func {
+            f.collect_functions(mc_version, files)
+        }
+    }
+    /// Collect ids of this function and its children by function tags which they are added to.
+    fn collect_tags(&self, tags:&mut BTreeMap<String, Vec<String>>) {
+        for tag in &self.tags {
+            tags.entry(tag.clone()).or_default().push(get_id(&self.namespace, &self.scope, &self.name));
+        }
         for f in &self.child_func {
-            f.save(funcs_path)?
+            f.collect_tags(tags)
         }
-        Ok(())
     }
-    pub fn build_datapack(&self, pack_name:&str, root_path:&str) -> std::io::Result<()> {
-        let pack_root = format!("{}/{}", root_path, pack_name);
-        // Clean up a root folder if the folder exists.
-        if fs::exists(&pack_root).unwrap() {
-            fs::remove_dir_all(&pack_root).unwrap();
+    /// Collect files of the datapack of this function and its children by their paths from the root of the pack.
+    /// 
+    /// Names of folders follow given version, like function or functions.
+    pub fn collect_files(&self, mc_version:McVersion) -> BTreeMap<String, String> {
+        let mut files:BTreeMap<String, String> = BTreeMap::new();
+        self.collect_functions(mc_version, &mut files);
+        // Function tags like data/minecraft/tags/function/tick.json
+        let mut tags:BTreeMap<String, Vec<String>> = BTreeMap::new();
+        self.collect_tags(&mut tags);
+        for (tag, values) in &tags {
+            let (namespace, name) = tag.split_once(':').unwrap_or(("minecraft", tag));
+            files.insert(
+                format!("data/{}/tags/{}/{}.json", namespace, mc_version.folder("function"), name),
+                generate_function_tag(values)
+            );
         }
-        fs::create_dir(&pack_root).unwrap();
-        
-        // Create pack.mcmeta
-        let mcmeta = vec![
-            ("pack_format", "61"),
-            ("description", "description")
+        files
+    }
+    /// Build a datapack of this function and its children for given version of Minecraft.
+    /// 
+    /// The pack_format and names of folders follow the version.
+    pub fn build_datapack(&self, pack_name:&str, root_path:&str, mc_version:McVersion) -> std::io::Result<()> {
+        let pack_root = prepare_pack_root(root_path, pack_name)?;
+        let pack = vec![
+            ("pack_format", mc_version.pack_format().to_string()),
+            ("description", "\"description\"".to_string())
         ];
-        make_a_file(
-            &pack_root,
-            "pack.mcmeta",
-            &generate_pack_mcmeta(mcmeta)
-        ).unwrap();
-
-        // Create data
-        fs::create_dir(format!("{}/data", &pack_root)).unwrap();
-        // Create data/<namespace>
-        fs::create_dir(format!("{}/data/{}", &pack_root, pack_name)).unwrap();
-        // Create data/<namespace>/function
-        let function_root = format!("{}/data/{}/function", &pack_root, pack_name);
-        fs::create_dir(&function_root).unwrap();
-        
-        self.save(&function_root)?;
-        Ok(())
+        make_a_file(&pack_root, "pack.mcmeta", &generate_pack_mcmeta(vec![("pack", pack)]))?;
+        write_files(&pack_root, &self.collect_files(mc_version))
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,120 @@
+//! Overlays of pack.mcmeta.
+//!
+//! A project can support other versions by supported_versions. The source is compiled for each of them,
+//! and files differing from the build for the oldest one are put onto overlays.
+
+use std::collections::BTreeMap;
+
+use super::{mcfunction, McVersion, MCFunction};
+
+#[test]
+fn overlay_test() {
+    let source = "x:int = random(1, 6); y:float = sin(30.0)";
+    let builds = ["1.20.3", "1.20.2"]
+        .iter()
+        .map(|f| {
+            let mut compiler = super::CompileTask::new();
+            compiler.mc_version = McVersion::parse(f).unwrap();
+            (compiler.mc_version, compiler.compile(source, "test").unwrap())
+        })
+        .collect::<Vec<(McVersion, MCFunction)>>();
+    let pack = OverlaidPack::plan(&builds).unwrap();
+    assert_eq!(pack.formats, (18, 26));
+    assert_eq!(pack.overlays.len(), 1);
+    let overlay = &pack.overlays[0];
+    println!("{:?}", overlay.files.keys());
+    assert_eq!(overlay.formats, (26, 26));
+    assert_eq!(overlay.directory, "overlay_26");
+    assert!(!pack.base["data/test/functions/mcpp_std/int/random.mcfunction"].contains("random value"));
+    assert!(overlay.files["data/test/functions/mcpp_std/int/random.mcfunction"].contains("random value"));
+    // Functions generated in the same way are only in the base.
+    assert!(!overlay.files.contains_key("data/test/functions/mcpp_std/float/sin.mcfunction"));
+    assert!(pack.base.contains_key("data/test/functions/mcpp_std/float/sin.mcfunction"));
+}
+
+/// Files which are used instead of ones of the base pack on a range of pack formats.
+pub struct Overlay {
+    pub directory : String,
+    pub formats : (u32, u32),
+    pub files : BTreeMap<String, String>
+}
+
+/// A datapack which works on several versions of Minecraft by overlays of pack.mcmeta.
+pub struct OverlaidPack {
+    pub formats : (u32, u32),
+    pub base : BTreeMap<String, String>,
+    pub overlays : Vec<Overlay>
+}
+
+/// The pure function to get a range of pack formats as JSON.
+fn format_range(formats:(u32, u32)) -> String {
+    format!("{{\"min_inclusive\":{},\"max_inclusive\":{}}}", formats.0, formats.1)
+}
+
+impl OverlaidPack {
+    /// Plan a pack from functions compiled for each version, then return None if nothing is given.
+    ///
+    /// The base is built for the oldest version, and each newer version has an overlay of files which differ from the base.
+    /// Each build is used until the format of the next one, so only one overlay is applied on a format,
+    /// and it has every difference from the base, not from the previous build.
+    /// Versions sharing a format use the build of the oldest of them.
+    pub fn plan(builds:&[(McVersion, MCFunction)]) -> Option<OverlaidPack> {
+        let mut builds = builds.iter().collect::<Vec<&(McVersion, MCFunction)>>();
+        builds.sort_by_key(|f| f.0);
+        builds.dedup_by_key(|f| f.0.pack_format());
+        let formats = builds.iter().map(|f| f.0.pack_format()).collect::<Vec<u32>>();
+        let (oldest, base_function) = builds.first()?;
+        let base = base_function.collect_files(*oldest);
+
+        let mut overlays:Vec<Overlay> = Vec::new();
+        for (i, (version, function)) in builds.iter().enumerate().skip(1) {
+            if *version < McVersion::OVERLAY {
+                println!("⚠️  Minecraft {} can't read overlays, so it uses the pack for {}.", version, oldest);
+                continue;
+            }
+            let files = function
+                .collect_files(*version)
+                .into_iter()
+                .filter(|(path, content)| base.get(path) != Some(content))
+                .collect::<BTreeMap<String, String>>();
+            if files.is_empty() {
+                continue;
+            }
+            overlays.push(Overlay {
+                directory : format!("overlay_{}", formats[i]),
+                formats : (formats[i], formats.get(i + 1).map(|f| f - 1).unwrap_or(formats[i])),
+                files : files
+            });
+        }
+        Some(OverlaidPack {
+            formats : (formats[0], formats[formats.len() - 1]),
+            base : base,
+            overlays : overlays
+        })
+    }
+    /// Build the pack onto root_path/pack_name, with pack.mcmeta listing the overlays.
+    pub fn build(&self, pack_name:&str, root_path:&str) -> std::io::Result<()> {
+        let pack_root = mcfunction::prepare_pack_root(root_path, pack_name)?;
+        let mut sections = vec![(
+            "pack",
+            vec![
+                ("pack_format", self.formats.0.to_string()),
+                ("supported_formats", format_range(self.formats)),
+                ("description", "\"description\"".to_string())
+            ]
+        )];
+        if !self.overlays.is_empty() {
+            let entries = self.overlays
+                .iter()
+                .map(|f| format!("{{\"formats\":{},\"directory\":\"{}\"}}", format_range(f.formats), f.directory))
+                .collect::<Vec<String>>();
+            sections.push(("overlays", vec![("entries", format!("[{}]", entries.join(",")))]));
+        }
+        mcfunction::make_a_file(&pack_root, "pack.mcmeta", &mcfunction::generate_pack_mcmeta(sections))?;
+        mcfunction::write_files(&pack_root, &self.base)?;
+        for overlay in &self.overlays {
+            mcfunction::write_files(&format!("{}/{}", pack_root, overlay.directory), &overlay.files)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,274 @@
+use super::{
+    evaluater::{self, EvaluateError},
+    scoreboard::{float, long, Types},
+    CompileTask, Scoreboard
+};
+
+#[test]
+fn print_test() {
+    let mut compiler = CompileTask::new();
+    let compiled = compiler.compile(
+        "hp:int = 5; max:int = 20; speed:float = 0 - 1.05; print(\"<red>HP: {hp} / {max}</> speed {speed}\")",
+        "test"
+    ).unwrap();
+    println!("{}", compiled.inside);
+    assert!(compiled.inside.contains(
+        "tellraw @a [\"\",{\"text\":\"HP: \",\"color\":\"red\"},{\"score\":{\"name\":\"#hp\",\"objective\":\"MCPP.var\"},\"color\":\"red\"}"
+    ));
+    assert!(compiled.inside.contains("execute if score #speed MCPP.var matches ..-1 run data modify storage mcpp:print slot5 set value '\"-\"'"));
+    assert!(compiled.inside.contains("{\"text\":\".\"},{\"score\":{\"name\":\"#Calc.PRINT.5.digit2\",\"objective\":\"MCPP.var\"}}"));
+
+    let mut compiler = CompileTask::new();
+    let compiled = compiler.compile("print_to(@a[tag=a,distance=..5], \"<bold><#00ff00>{1 + 2}\")", "test").unwrap();
+    println!("{}", compiled.inside);
+    assert!(compiled.inside.contains("tellraw @a[tag=a,distance=..5] [\"\",{\"score\":{\"name\":\"#Calc.PRINT.0.value\",\"objective\":\"MCPP.var\"},\"bold\":true,\"color\":\"#00ff00\"}]"));
+    assert!(CompileTask::new().compile("print(\"<rainbow>hi\")", "test").is_err());
+
+    let mut compiler = CompileTask::new();
+    let compiled = compiler.compile("b:bool = true; l:long = 3000000000; print(\"{b} {l}\")", "test").unwrap();
+    println!("{}", compiled.inside);
+    assert!(compiled.inside.contains("execute if score #b MCPP.var matches 0 run data modify storage mcpp:print slot0 set value '\"false\"'"));
+    assert!(compiled.inside.contains("{\"nbt\":\"slot2_digits\",\"storage\":\"mcpp:print\",\"interpret\":true}"));
+}
+
+/// The data storage where texts decided in runtime are kept while a line is printed, like `mcpp:print slot0`.
+pub const PRINT_STORAGE:&str = "mcpp:print";
+
+/// Named colors of Minecraft, which can be used as tags like `<red>`.
+const COLORS:[&str; 16] = [
+    "black", "dark_blue", "dark_green", "dark_aqua", "dark_red", "dark_purple", "gold", "gray",
+    "dark_gray", "blue", "green", "aqua", "red", "light_purple", "yellow", "white"
+];
+/// Styles of Minecraft, which can be used as tags like `<bold>`.
+const STYLES:[&str; 5] = ["bold", "italic", "underlined", "strikethrough", "obfuscated"];
+
+/// A piece of a printed text, which is a literal text or a formula written in braces.
+enum Piece {
+    Text(String),
+    Value(String)
+}
+
+/// Split the arguments of `print(...)` or `print_to(...)` onto the target and the text.
+///
+/// None will be returned if the line isn't a print.
+pub fn parse_call(formula:&str) -> Option<Result<(String, String), EvaluateError>> {
+    let formula = formula.trim();
+    let (args, has_target) = match (formula.strip_prefix("print_to"), formula.strip_prefix("print")) {
+        (Some(args), _) if args.trim_start().starts_with('(') => (args, true),
+        (_, Some(args)) if args.trim_start().starts_with('(') => (args, false),
+        _ => return None
+    };
+    let invalid = || EvaluateError::InvalidPrint(formula.to_string());
+    let parse = || -> Result<(String, String), EvaluateError> {
+        let inner = args.trim().strip_prefix('(').and_then(|f| f.strip_suffix(')')).ok_or_else(invalid)?;
+        let quote = inner.find('"').ok_or_else(invalid)?;
+        let target = match (inner[..quote].trim().strip_suffix(','), has_target) {
+            (Some(target), true) if !target.trim().is_empty() => target.trim().to_string(),
+            (None, false) if inner[..quote].trim().is_empty() => "@a".to_string(),
+            _ => return Err(invalid())
+        };
+        let text = inner[quote..]
+            .trim()
+            .strip_prefix('"')
+            .and_then(|f| f.strip_suffix('"'))
+            .ok_or_else(invalid)?
+            .replace("\\\"", "\"")
+            .replace("\\\\", "\\");
+        Ok((target, text))
+    };
+    Some(parse())
+}
+/// The pure function to parse a text onto pieces and the styles of them.
+///
+/// `{formula}` is replaced by the value of it, and tags like `<red>`, `<#ff8000>` or `<bold>` style the text after them
+/// until `</>` closes the last tag. `{{`, `}}` and `<<` are written as `{`, `}` and `<`.
+fn parse_text(text:&str) -> Result<Vec<(Piece, String)>, EvaluateError> {
+    let mut pieces:Vec<(Piece, String)> = Vec::new();
+    let mut tags:Vec<String> = Vec::new();
+    let mut literal = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let escaped = matches!((c, chars.peek()), ('{', Some('{')) | ('}', Some('}')) | ('<', Some('<')));
+        if escaped || (c != '{' && c != '<') {
+            if escaped {
+                chars.next();
+            }
+            literal.push(c);
+            continue;
+        }
+        let close = if c == '{' { '}' } else { '>' };
+        let inside:String = chars.by_ref().take_while(|f| *f != close).collect();
+        let style = style_json(&tags);
+        if !literal.is_empty() {
+            pieces.push((Piece::Text(std::mem::take(&mut literal)), style.clone()));
+        }
+        match (c, inside.trim()) {
+            ('{', formula) => pieces.push((Piece::Value(formula.to_string()), style)),
+            (_, tag) if tag.starts_with('/') => { tags.pop(); },
+            (_, tag) if COLORS.contains(&tag) || STYLES.contains(&tag) || is_hex_color(tag) => tags.push(tag.to_string()),
+            (_, tag) => return Err(EvaluateError::UnknownMarkupTag(tag.to_string()))
+        }
+    }
+    if !literal.is_empty() {
+        pieces.push((Piece::Text(literal), style_json(&tags)));
+    }
+    Ok(pieces)
+}
+fn is_hex_color(tag:&str) -> bool {
+    tag.strip_prefix('#').is_some_and(|f| f.len() == 6 && f.chars().all(|c| c.is_ascii_hexdigit()))
+}
+/// The pure function to get fields of JSON text for given tags, like `,"color":"red","bold":true`.
+///
+/// The last color wins, as a tag inside another tag does.
+fn style_json(tags:&[String]) -> String {
+    let mut fields:Vec<String> = tags
+        .iter()
+        .filter(|f| STYLES.contains(&f.as_str()))
+        .map(|f| format!(",\"{}\":true", f))
+        .collect();
+    if let Some(color) = tags.iter().rev().find(|f| !STYLES.contains(&f.as_str())) {
+        fields.push(format!(",\"color\":\"{}\"", color));
+    }
+    fields.concat()
+}
+/// The pure function to add fields of a style onto a JSON text component.
+fn styled(component:&str, style:&str) -> String {
+    format!("{}{}}}", &component[..component.len() - 1], style)
+}
+/// The pure function to get a JSON text component showing a slot of the storage.
+fn slot_json(slot:&str) -> String {
+    format!("{{\"nbt\":\"{}\",\"storage\":\"{}\",\"interpret\":true}}", slot, PRINT_STORAGE)
+}
+/// The pure function to get a command to set a slot to a JSON text, if a condition is given, only when it's met.
+fn set_slot(slot:&str, condition:Option<&str>, json:&str) -> String {
+    let command = format!("data modify storage {} {} set value '{}'", PRINT_STORAGE, slot, json);
+    match condition {
+        Some(condition) => format!("execute if {} run {}", condition, command),
+        None => command
+    }
+}
+/// The function to get commands to print a line by tellraw.
+pub fn compile(compiler:&CompileTask, target:&str, text:&str) -> Result<Vec<String>, EvaluateError> {
+    let mut commands:Vec<String> = Vec::new();
+    // The first element is the parent of the others, so it is empty to keep styles of them apart.
+    let mut components:Vec<String> = vec!["\"\"".to_string()];
+    for (i, (piece, style)) in parse_text(text)?.into_iter().enumerate() {
+        match piece {
+            Piece::Text(literal) => components.push(format!(
+                "{{\"text\":\"{}\"{}}}",
+                literal.replace('\\', "\\\\").replace('"', "\\\""),
+                style
+            )),
+            Piece::Value(formula) => {
+                let (mut prepared, shown) = value_components(compiler, &formula, i)?;
+                commands.append(&mut prepared);
+                components.extend(shown.iter().map(|f| styled(f, &style)));
+            }
+        }
+    }
+    commands.push(format!("tellraw {} [{}]", target, components.join(",")));
+    Ok(commands)
+}
+/// The function to check types of values in a printed text.
+pub fn check(compiler:&CompileTask, text:&str) -> Result<(), EvaluateError> {
+    for (piece, _) in parse_text(text)? {
+        if let Piece::Value(formula) = piece {
+            match evaluater::guess_type(compiler, &formula)? {
+                Types::Int | Types::Flt(_) | Types::Lng | Types::Bln => (),
+                Types::Non => return Err(EvaluateError::VoidValueUsed),
+                t => return Err(EvaluateError::UnprintableType(t))
+            }
+        }
+    }
+    Ok(())
+}
+/// Get a scoreboard used to print the i-th piece of a line, named like #Calc.PRINT.0.value.
+fn work_score(index:usize, name:&str, data_type:Types) -> Scoreboard {
+    Scoreboard {
+        name : name.to_string(),
+        data_type,
+        scope : vec!["Calc".to_string(), "PRINT".to_string(), index.to_string()]
+    }
+}
+/// The function to get commands to prepare a value, and JSON text components showing it.
+///
+/// A variable is shown as it is, and other formulas are evaluated onto a work scoreboard at first.
+fn value_components(compiler:&CompileTask, formula:&str, index:usize) -> Result<(Vec<String>, Vec<String>), EvaluateError> {
+    let mut commands:Vec<String> = Vec::new();
+    let value = match compiler.get_variable(&formula.to_string()) {
+        Some(variable) => variable.clone(),
+        None => {
+            let value = work_score(index, "value", evaluater::guess_type(compiler, formula)?);
+            commands.append(&mut evaluater::eval_then_store(compiler, &value, formula)?);
+            value
+        }
+    };
+    let slot = format!("slot{}", index);
+    let shown = match value.data_type {
+        Types::Int => vec![value.pure_score_json()],
+        Types::Bln => {
+            commands.push(set_slot(&slot, None, "\"true\""));
+            commands.push(set_slot(&slot, Some(&value.pure_match_range("0")), "\"false\""));
+            vec![slot_json(&slot)]
+        },
+        Types::Flt(p) => {
+            let magnif = float::get_magnif(p);
+            let (abs, integer, fraction) = (
+                work_score(index, "abs", Types::Int),
+                work_score(index, "integer", Types::Int),
+                work_score(index, "fraction", Types::Int)
+            );
+            let negative = value.pure_match_range("..-1");
+            commands.append(&mut vec![
+                set_slot(&slot, None, "\"\""),
+                set_slot(&slot, Some(&negative), "\"-\""),
+                abs.pure_assign_score(&value),
+                format!("execute if {} run {}", negative, abs.pure_assign_value(0)),
+                format!("execute if {} run {}", negative, abs.pure_calc_score("-", &value)),
+                integer.pure_assign_score(&abs),
+                integer.pure_calc_num("/", magnif)?,
+                fraction.pure_assign_score(&abs),
+                fraction.pure_calc_num("%", magnif)?
+            ]);
+            let mut shown = vec![slot_json(&slot), integer.pure_score_json()];
+            if p != 0 {
+                shown.push("{\"text\":\".\"}".to_string());
+                shown.append(&mut digits(&fraction, p, index, &mut commands)?);
+            }
+            shown
+        },
+        // The low half is zero-padded only when the high half is shown before it.
+        Types::Lng => {
+            let abs = work_score(index, "abs", Types::Lng);
+            let (abs_hi, abs_lo) = long::halves(&abs);
+            let (hi, _) = long::halves(&value);
+            let negative = hi.pure_match_range("..-1");
+            commands.append(&mut long::abs(&value, &abs)?);
+            let padded = [vec!["\"\"".to_string(), abs_hi.pure_score_json()], digits(&abs_lo, 9, index, &mut commands)?].concat();
+            commands.append(&mut vec![
+                set_slot(&slot, None, "\"\""),
+                set_slot(&slot, Some(&negative), "\"-\""),
+                set_slot(&format!("{}_digits", slot), None, &format!("[{}]", padded.join(","))),
+                set_slot(&format!("{}_digits", slot), Some(&abs_hi.pure_match_range("0")), &abs_lo.pure_score_json())
+            ]);
+            vec![slot_json(&slot), slot_json(&format!("{}_digits", slot))]
+        },
+        Types::Non => return Err(EvaluateError::VoidValueUsed),
+        t => return Err(EvaluateError::UnprintableType(t))
+    };
+    Ok((commands, shown))
+}
+/// The function to get JSON text components showing each digits of a score, so leading zeros are shown.
+fn digits(score:&Scoreboard, count:u32, index:usize, commands:&mut Vec<String>) -> Result<Vec<String>, EvaluateError> {
+    let mut shown:Vec<String> = Vec::new();
+    for k in (0..count).rev() {
+        let digit = work_score(index, &format!("digit{}", k), Types::Int);
+        commands.push(digit.pure_assign_score(score));
+        if k != 0 {
+            commands.push(digit.pure_calc_num("/", 10_i32.pow(k))?);
+        }
+        commands.push(digit.pure_calc_num("%", 10)?);
+        shown.push(digit.pure_score_json());
+    }
+    Ok(shown)
+}
@@ -0,0 +1,285 @@
+//! Sentences and formulas which run later.
+//!
+//! `after 20t {...}` runs the inside later by `schedule`, and `after 20t as @s {...}` runs it as the executor of now.
+//! Locals of enclosing sentences read inside are saved with it.
+//!
+//! `every 40t {...}` on the top level runs the inside periodically from a tick function, and ones of the same period are batched.
+//!
+//! `wait(10t)` in a function splits the rest of it onto a continuation, which is scheduled with local variables saved.
+
+use std::collections::BTreeMap;
+
+use super::{mcfunction, scoreboard::Types, Line, MCFunction, Scoreboard, Sentence, SentenceError};
+
+#[test]
+fn schedule_test() {
+    let mut compiler = super::CompileTask::new();
+    let compiled = compiler.compile("a:int = 1; after 5s { a = 2 }; after 20t as @a[tag=x] { run!(\"say @s\") }", "test").unwrap();
+    println!("{}", compiled.inside);
+    assert!(compiled.inside.contains("append"));
+    assert!(compiled.inside.contains("schedule function test:"));
+    assert!(compiled.inside.contains(" 5s append"));
+    let carry = compiled.child_func
+        .iter()
+        .flat_map(|f| &f.child_func)
+        .find(|f| f.name.ends_with("_carry"))
+        .unwrap();
+    println!("{}", carry.inside);
+    assert!(carry.inside.starts_with("execute as @e[tag=MCPP.after."));
+    assert!(compiled.inside.contains("tag @a[tag=x] add MCPP.after."));
+
+    assert_eq!(After::parse(&["3d".to_string()]).unwrap().delay, "3d");
+    assert!(After::parse(&["3m".to_string()]).is_err());
+    assert!(After::parse(&["3t".to_string(), "as".to_string()]).is_err());
+}
+#[test]
+fn after_capture_test() {
+    let mut compiler = super::CompileTask::new();
+    let compiled = compiler.compile("y:int = 0; fn f() { x:int = 5; after 1s { y = x }; z:int = 1 }", "test").unwrap();
+    let f = compiled.child_func.iter().find(|f| f.name == "f").unwrap();
+    let after = &f.child_func[0];
+    println!("{}", f.inside);
+    assert!(f.inside.contains(&format!("execute store result storage mcpp:wait {}[-1].x int 1 run scoreboard players get #x MCPP.var", after.name)));
+    assert!(!f.inside.contains(&format!("{}[-1].z", after.name)));
+    let mut simulator = super::simulator::Simulator::new(&compiled);
+    simulator.call("test:f");
+    // x is freed when f ends, and the scheduled block gets it back from the storage.
+    simulator.call(&mcfunction::get_id(&after.namespace, &after.scope, &after.name));
+    assert_eq!(simulator.score("#y"), Some(5));
+}
+#[test]
+fn wait_test() {
+    let mut compiler = super::CompileTask::new();
+    let compiled = compiler.compile("fn scene() { a:int = 1; wait(10t); a = a + 1; wait(1s); b:int = a }", "test").unwrap();
+    let scene = compiled.child_func.iter().find(|f| f.name == "scene").unwrap();
+    println!("{}", scene.inside);
+    assert!(scene.inside.contains("data modify storage mcpp:wait scene_wait1 append value {}"));
+    assert!(scene.inside.contains("execute store result storage mcpp:wait scene_wait1[-1].a int 1 run scoreboard players get #a MCPP.var"));
+    assert!(scene.inside.ends_with("schedule function test:scene_wait1 10t append"));
+    let first = scene.child_func.iter().find(|f| f.name == "scene_wait1").unwrap();
+    println!("{}", first.inside);
+    assert!(first.inside.starts_with("execute store result score #a MCPP.var run data get storage mcpp:wait scene_wait1[0].a"));
+    assert!(first.inside.contains("schedule function test:scene_wait2 1s append"));
+    let second = scene.child_func.iter().find(|f| f.name == "scene_wait2").unwrap();
+    println!("{}", second.inside);
+    assert!(second.inside.contains("scoreboard players reset #b MCPP.var"));
+
+    // A misplaced wait is reported by the type check, with the message of InvalidWait.
+    let invalid_wait = SentenceError::InvalidWait("wait(1t)".to_string()).to_string();
+    let is_invalid_wait = |raw:&str| matches!(
+        super::CompileTask::new().compile(raw, "test"),
+        Err(SentenceError::TypeErrors(e)) if e.iter().any(|f| f.ends_with(&invalid_wait))
+    );
+    assert!(is_invalid_wait("fn f() -> int { wait(1t); return 1 }"));
+    assert!(is_invalid_wait("fn f() { if 1 { wait(1t) } }"));
+    assert!(super::CompileTask::new().compile("fn f() { wait(1m) }", "test").is_err());
+}
+#[test]
+fn every_test() {
+    let mut compiler = super::CompileTask::new();
+    let compiled = compiler.compile("every 40t { run!(\"say a\") }; every 2s { run!(\"say b\") }; every 1t { run!(\"say c\") }", "test").unwrap();
+    let tick = compiled.child_func.iter().find(|f| f.name == TICK_FUNCTION).unwrap();
+    println!("{}", tick.inside);
+    assert_eq!(tick.tags, vec![TICK_TAG.to_string()]);
+    assert!(tick.inside.contains("scoreboard players add #MCPP.every.40 MCPP.var 1"));
+    assert!(tick.inside.contains("execute if score #MCPP.every.40 MCPP.var matches 40.. run function test:every_40t"));
+    // 40t and 2s share a period, so they are batched into one function.
+    assert_eq!(tick.child_func.len(), 2);
+    let batch = tick.child_func.iter().find(|f| f.name == "every_40t").unwrap();
+    println!("{}", batch.inside);
+    assert!(batch.inside.starts_with("scoreboard players set #MCPP.every.40 MCPP.var 0"));
+    assert_eq!(batch.inside.lines().filter(|f| f.starts_with("function test:")).count(), 2);
+    assert!(!compiled.inside.contains("function test:"));
+
+    assert_eq!(to_ticks("1d"), 24000);
+    assert!(super::CompileTask::new().compile("fn f() { every 1s { } }", "test").is_err());
+    assert!(super::CompileTask::new().compile("every 1s as @a { }", "test").is_err());
+}
+
+/// The storage which keeps variables saved for scheduled functions.
+pub const WAIT_STORAGE:&str = "mcpp:wait";
+
+/// The pure function to check a duration of Minecraft, like 20t, 5s or 1d.
+pub fn parse_duration(raw:&str) -> Result<String, SentenceError> {
+    let raw = raw.trim();
+    let valid = raw
+        .strip_suffix(['t', 's', 'd'])
+        .is_some_and(|f| f.parse::<u32>().is_ok_and(|n| n > 0));
+    if valid { Ok(raw.to_string()) } else { Err(SentenceError::InvalidDuration(raw.to_string())) }
+}
+
+/// The specifier of `after 20t {...}`, or `after 20t as @s {...}` which carries the executor along.
+pub struct After {
+    pub delay : String,
+    pub carried : Option<String>
+}
+impl After {
+    pub fn parse(specifiers:&[String]) -> Result<After, SentenceError> {
+        match specifiers {
+            [delay] => Ok(After { delay : parse_duration(delay)?, carried : None }),
+            [delay, as_, selector @ ..] if as_ == "as" && !selector.is_empty() => Ok(After {
+                delay : parse_duration(delay)?,
+                carried : Some(selector.join(" "))
+            }),
+            _ => Err(SentenceError::InvalidDuration(specifiers.join(" ")))
+        }
+    }
+    /// The tag put on carried entities until the scheduled function runs.
+    pub fn tag(name:&str) -> String {
+        format!("MCPP.after.{}", name)
+    }
+    /// Get commands to schedule given function, and a function which runs it as carried entities if needed.
+    ///
+    /// A scheduled function runs without an executor, so carried entities are tagged,
+    /// then the function runs as each of them and the function itself removes the tag.
+    /// Entities tagged before a scheduled run are carried by it, even if they were tagged by a later one.
+    /// Captured variables are saved by [suspend] and restored once at the head of the scheduled function.
+    pub fn schedule(&self, function:&mut MCFunction, captured:&[(&String, &Scoreboard)]) -> (String, Option<MCFunction>) {
+        let tag = After::tag(&function.name);
+        let mut carry = self.carried.as_ref().map(|_| MCFunction::generate(
+            &format!("{}_carry", function.name),
+            format!("execute as @e[tag={}] at @s run {}", tag, function.callment),
+            &function.namespace,
+            &function.scope
+        ));
+        let scheduled = carry.as_mut().unwrap_or(function);
+        let mut commands:Vec<String> = self.carried
+            .iter()
+            .map(|selector| format!("tag {} add {}", selector, tag))
+            .collect();
+        if captured.is_empty() {
+            commands.push(format!(
+                "schedule function {} {} append",
+                mcfunction::get_id(&scheduled.namespace, &scheduled.scope, &scheduled.name),
+                self.delay
+            ));
+        } else {
+            commands.extend(suspend(scheduled, &self.delay, captured));
+            let mut inside = resume(scheduled, captured);
+            inside.push(std::mem::take(&mut scheduled.inside));
+            scheduled.inside = inside.join("\n");
+        }
+        (commands.join("\n"), carry)
+    }
+}
+
+/// The pure function to get the duration of `wait(10t)`, if the formula is a wait.
+pub fn parse_wait(formula:&str) -> Option<Result<String, SentenceError>> {
+    let inside = formula
+        .trim()
+        .strip_prefix("wait")?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+    Some(parse_duration(inside))
+}
+/// The name of the continuation which runs the rest of a function after the index-th wait, like name_wait1.
+pub fn continuation_name(name:&str, index:usize) -> String {
+    format!("{}_wait{}", name, index)
+}
+/// The path of the queue of saved variables of a scheduled function in [WAIT_STORAGE].
+fn queue_path(function:&MCFunction) -> String {
+    let mut path = function.scope.clone();
+    path.push(function.name.clone());
+    path.join(".")
+}
+/// The pure function to get commands to save variables onto the queue of given function, then schedule it.
+///
+/// Each schedule appends its variables, and each run takes the first of them.
+/// Functions scheduled with the same delay run in the order they are scheduled,
+/// so a function scheduled in many calls at once gets its own variables back.
+pub fn suspend(function:&MCFunction, delay:&str, variables:&[(&String, &Scoreboard)]) -> Vec<String> {
+    let path = queue_path(function);
+    let mut commands = vec![format!("data modify storage {} {} append value {{}}", WAIT_STORAGE, path)];
+    for (name, variable) in variables {
+        commands.push(variable.pure_store_to_storage(WAIT_STORAGE, &format!("{}[-1].{}", path, name)));
+    }
+    commands.push(format!(
+        "schedule function {} {} append",
+        mcfunction::get_id(&function.namespace, &function.scope, &function.name),
+        delay
+    ));
+    commands
+}
+/// The pure function to get commands to restore variables saved by [suspend] at the head of the scheduled function.
+pub fn resume(function:&MCFunction, variables:&[(&String, &Scoreboard)]) -> Vec<String> {
+    let path = queue_path(function);
+    let mut commands:Vec<String> = variables
+        .iter()
+        .map(|(name, variable)| variable.pure_load_from_storage(WAIT_STORAGE, &format!("{}[0].{}", path, name)))
+        .collect();
+    commands.push(format!("data remove storage {} {}[0]", WAIT_STORAGE, path));
+    commands
+}
+
+/// The name of the tick function which runs periodic tasks.
+pub const TICK_FUNCTION:&str = "mcpp_tick";
+/// The function tag which runs functions every tick.
+pub const TICK_TAG:&str = "minecraft:tick";
+
+/// The pure function to get the number of ticks of a duration checked by [parse_duration].
+pub fn to_ticks(duration:&str) -> u32 {
+    let (number, unit) = duration.split_at(duration.len() - 1);
+    let magnif = match unit {
+        "s" => 20,
+        "d" => 24000,
+        _ => 1
+    };
+    number.parse::<u32>().unwrap_or(0).saturating_mul(magnif)
+}
+/// Get the period of `every 40t {...}` in ticks.
+pub fn parse_every(specifiers:&[String]) -> Result<u32, SentenceError> {
+    match specifiers {
+        [period] => Ok(to_ticks(&parse_duration(period)?)),
+        _ => Err(SentenceError::InvalidDuration(specifiers.join(" ")))
+    }
+}
+/// Find an `every` sentence inside of given sentence.
+fn find_every(sentence:&Sentence) -> Option<&Sentence> {
+    sentence.parsed_lines
+        .iter()
+        .filter_map(|f| match f {
+            Line::Sentence(s) => Some(s),
+            _ => None
+        })
+        .find_map(|f| if f.is_every() { Some(f) } else { find_every(f) })
+}
+/// Check periods of `every` sentences, and that they are only on the top level.
+pub(super) fn check_every(root:&Sentence) -> Result<(), SentenceError> {
+    for line in &root.parsed_lines {
+        let Line::Sentence(sentence) = line else { continue };
+        if sentence.is_every() {
+            parse_every(&sentence.specifiers[1..])?;
+        }
+        if let Some(nested) = find_every(sentence) {
+            return Err(SentenceError::EveryOutsideOfTopLevel(nested.specifiers.join(" ")))
+        }
+    }
+    Ok(())
+}
+/// Generate the tick function which runs callments of periodic tasks, batched by their periods.
+///
+/// Each period has a counter added every tick, and the function of the period resets it, then runs the tasks.
+/// Counters are scoreboards, so periods go on through /reload.
+pub fn generate_periodic(tasks:&BTreeMap<u32, Vec<String>>, namespace:&str, scope:&[String]) -> Result<MCFunction, SentenceError> {
+    let mut tick = MCFunction::generate(TICK_FUNCTION, String::new(), namespace, scope);
+    let mut inside:Vec<String> = Vec::new();
+    for (period, callments) in tasks {
+        let counter = Scoreboard {
+            name : period.to_string(),
+            data_type : Types::Int,
+            scope : vec!["MCPP".to_string(), "every".to_string()]
+        };
+        let mut batch = MCFunction::generate(&format!("every_{}t", period), String::new(), namespace, scope);
+        batch.inside = std::iter::once(counter.pure_assign_value(0))
+            .chain(callments.iter().cloned())
+            .collect::<Vec<String>>()
+            .join("\n");
+        inside.push(counter.pure_calc_num("+", 1).map_err(SentenceError::InvalidFormula)?);
+        inside.push(format!("execute if {} run {}", counter.pure_match_range(&format!("{}..", period)), batch.callment));
+        tick.child_func.push(batch);
+    }
+    tick.inside = inside.join("\n");
+    tick.tags.push(TICK_TAG.to_string());
+    Ok(tick)
+}
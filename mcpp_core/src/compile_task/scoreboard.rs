@@ -1,10 +1,17 @@
 mod int;
-mod float;
+pub mod float;
 mod bool;
+mod enumeration;
+pub mod long;
+mod cast;
+pub mod overflow;
 
 use std::{fmt, vec};
 
-use super::{evaluater::EvaluateError, MCFunction};
+use super::{evaluater::EvaluateError, Enumeration, MCFunction, Structure};
+
+pub use cast::Rounding;
+pub use overflow::Overflow;
 
 const NAMESPACE:&str = "MCPP.var";
 
@@ -12,7 +19,7 @@ const NAMESPACE:&str = "MCPP.var";
 fn compare_test() {
     let hoge = Scoreboard {
         name : "Hoge".to_string(),
-        data_type : Types::Flt,
+        data_type : Types::Flt(float::DEFAULT_PRECISION),
         scope : vec!["TEST".to_string()]
     };
     println!("{:?}", hoge.compare(">=", &Calcable::Int(810)).unwrap());
@@ -27,8 +34,11 @@ Scoreboard {
 }
 pub enum Calcable<'a> {
     Int(i32),
-    Flt(f32),
+    /// A float literal and the precision it is typed as.
+    Flt(f64, u32),
+    Lng(i64),
     Bln(bool),
+    Enm(&'a Enumeration, i32),
     Scr(&'a Scoreboard),
     Mcf(&'a MCFunction)
 }
@@ -36,28 +46,37 @@ impl Calcable<'_> {
     pub fn get_type(&self) -> Types {
         match self {
             Self::Int(_) => Types::Int,
-            Self::Flt(_) => Types::Flt,
+            Self::Flt(_, p) => Types::Flt(*p),
+            Self::Lng(_) => Types::Lng,
             Self::Bln(_) => Types::Bln,
+            Self::Enm(e, _) => Types::Enum((*e).clone()),
             Self::Scr(s) => s.data_type.clone(),
             Self::Mcf(f) => f.ret_container.data_type.clone()
         }
     }
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Types {
     Int, // Int
-    Flt, // Float
+    Flt(u32), // Float, with digits under the decimal point
+    Lng, // Long, stored as two scoreboards, in about ±2.1e18
     Bln, // Boolean
     Non, // None
+    Struct(Structure), // User-defined structure
+    Enum(Enumeration), // User-defined enumeration, stored as int
 }
 impl fmt::Display for Types {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f, "{}", match self {
-                Self::Int => "int",
-                Self::Flt => "float",
-                Self::Bln => "bool",
-                Self::Non => "none"
+                Self::Int => "int".to_string(),
+                Self::Flt(p) if *p == float::DEFAULT_PRECISION => "float".to_string(),
+                Self::Flt(p) => format!("fixed<{}>", p),
+                Self::Lng => "long".to_string(),
+                Self::Bln => "bool".to_string(),
+                Self::Non => "none".to_string(),
+                Self::Struct(s) => s.name.clone(),
+                Self::Enum(e) => e.name.clone()
             }
         )
     }
@@ -67,13 +86,16 @@ impl fmt::Display for Calcable<'_> {
         write!(f, "{}", match self {
             Calcable::Int(i) => i.to_string(),
             Calcable::Scr(s) => s.to_string(),
-            Calcable::Flt(f) => f.to_string(),
+            Calcable::Flt(f, _) => f.to_string(),
+            Calcable::Lng(l) => l.to_string(),
             Calcable::Bln(b) => b.to_string(),
+            Calcable::Enm(e, i) => format!("{}::{}", e.name, e.variants[*i as usize]),
             Calcable::Mcf(f) => format!("{}(...)", f.name)
         })
     }
 }
 impl From<i32> for Scoreboard {
+    /// Get the constant holding given value, which is set on load.
     fn from(value: i32) -> Self {
         return Scoreboard {
             name  : value.to_string(),
@@ -89,9 +111,24 @@ impl PartialEq for Scoreboard {
     }
 }
 
+/// Get the flag which tells a block left its function early, like by return.
+/// 
+/// It is set right before leaving, and cleared around a block which can leave,
+/// so the parent of the block leaves as well only if the block set it.
+pub fn get_exit_flag() -> Scoreboard {
+    Scoreboard {
+        name : "EXIT".to_string(),
+        data_type : Types::Int,
+        scope : vec!["Calc".to_string()]
+    }
+}
+
+/// Get a temporary scoreboard used while converting a value between types.
+/// 
+/// It is not #Calc.TEMP, which keeps the result of a formula.
 pub fn get_temp_score() -> Scoreboard {
     Scoreboard {
-        name : "TEMP".to_string(),
+        name : "CAST".to_string(),
         data_type : Types::Int,
         scope : vec!["Calc".to_string()]
     }
@@ -105,21 +142,36 @@ impl Scoreboard {
         return format!("{}{}", "#", last.join("."));
     }
     pub fn calc(&self, operator:&str, source:&Calcable) -> Result<String, EvaluateError> {
+        self.calc_with(operator, source, Overflow::Wrapping)
+    }
+    /// Calc with given value, handling an overflow of int and float by given mode.
+    pub fn calc_with(&self, operator:&str, source:&Calcable, overflow:Overflow) -> Result<String, EvaluateError> {
         match self.data_type {
-            Types::Int => int::calc(&self, operator, source),
-            Types::Flt => float::calc(&self, operator, source),
+            Types::Int => int::calc(&self, operator, source, overflow),
+            Types::Flt(_) => float::calc(&self, operator, source, overflow),
+            Types::Lng => long::calc(self, operator, source),
             Types::Bln => bool::calc(self, operator, source),
-            Types::Non => Err(EvaluateError::OperationOccuredBetweenUnsupportedTypes(Types::Non, source.get_type()))
+            Types::Non | Types::Struct(_) | Types::Enum(_) => Err(
+                EvaluateError::OperationOccuredBetweenUnsupportedTypes(self.data_type.clone(), source.get_type())
+            )
         }
     }
     pub fn assign(&self, source:&Calcable) -> Result<String, EvaluateError> {
         match self.data_type {
             Types::Int => int::assign(&self, source),
-            Types::Flt => float::assign(&self, source),
+            Types::Flt(_) => float::assign(&self, source),
+            Types::Lng => long::assign(self, source),
             Types::Bln => bool::assign(&self, source),
-            Types::Non => Err(EvaluateError::AssignOccuredBetweenUnsupportedTypes(source.get_type(), Types::Non))
+            Types::Enum(_) => enumeration::assign(&self, source),
+            Types::Non | Types::Struct(_) => Err(
+                EvaluateError::AssignOccuredBetweenUnsupportedTypes(source.get_type(), self.data_type.clone())
+            )
         }
     }
+    /// Store given value onto this scoreboard, converting it onto the type of this explicitly.
+    pub fn cast(&self, source:&Calcable, rounding:Rounding) -> Result<String, EvaluateError> {
+        cast::cast(self, source, rounding)
+    }
     pub fn compare(&self, operator:&str, source:&Calcable) -> Result<(String, String), EvaluateError> {
         let mut do_invert = false;
         let _oper = if operator == "!=" {
@@ -130,8 +182,10 @@ impl Scoreboard {
         };
         let result = match self.data_type {
             Types::Int => int::compare(self, _oper, source),
-            Types::Flt => float::compare(self, _oper, source),
+            Types::Flt(_) => float::compare(self, _oper, source),
+            Types::Lng => long::compare(self, _oper, source),
             Types::Bln => Ok((String::new(), bool::compare(&self, _oper, source)?)),
+            Types::Enum(_) => Ok((String::new(), enumeration::compare(&self, _oper, source)?)),
             _ => Err(EvaluateError::ComparementOccuredBetweenUnsupportedTypes(self.data_type.clone(), source.get_type()))
         }?;
         Ok(
@@ -145,8 +199,37 @@ impl Scoreboard {
             )
         )
     }
+    /// The pure function to get scoreboards of each fields.
+    /// 
+    /// The fields are scoped under the name of this scoreboard,
+    /// so `pos.x` of `pos:Vec3` will be #pos.x in Minecraft.
+    /// 
+    /// It returns an empty vector if this is not a structure.
+    pub fn fields(&self) -> Vec<Scoreboard> {
+        match &self.data_type {
+            Types::Struct(s) => {
+                let mut scope = self.scope.clone();
+                scope.push(self.name.clone());
+                s.fields
+                    .iter()
+                    .map(|f| Scoreboard {
+                        name : f.0.clone(),
+                        data_type : f.1.clone(),
+                        scope : scope.clone()
+                    })
+                    .collect()
+            },
+            _ => Vec::new()
+        }
+    }
     pub fn free(&self) -> String {
-        format!("scoreboard players reset {} {}", self.mcname(), NAMESPACE)
+        match self.data_type {
+            Types::Lng => {
+                let (hi, lo) = long::halves(self);
+                format!("{}\n{}", hi.free(), lo.free())
+            },
+            _ => format!("scoreboard players reset {} {}", self.mcname(), NAMESPACE)
+        }
     }
     
     pub fn pure_calc_num(&self, operator:&str, num:i32) -> Result<String, EvaluateError> {
@@ -162,13 +245,7 @@ impl Scoreboard {
             ),
             _ => {
                 let source = Scoreboard::from(num);
-                Ok(
-                    format!(
-                        "{}\n{}",
-                        source.assign(&Calcable::Int(num))?,
-                        int::calc(&self, operator, &Calcable::Scr(&source))?
-                    )
-                )
+                int::calc(&self, operator, &Calcable::Scr(&source), Overflow::Wrapping)
             }
         }
     }
@@ -182,6 +259,26 @@ impl Scoreboard {
             NAMESPACE
         )
     }
+    /// The pure function to get a command to keep the smaller of this and given scoreboard.
+    pub fn pure_min_score(&self, value:&Scoreboard) -> String {
+        format!(
+            "scoreboard players operation {} {} < {} {}",
+            self.mcname(),
+            NAMESPACE,
+            value.mcname(),
+            NAMESPACE
+        )
+    }
+    /// The pure function to get a command to keep the larger of this and given scoreboard.
+    pub fn pure_max_score(&self, value:&Scoreboard) -> String {
+        format!(
+            "scoreboard players operation {} {} > {} {}",
+            self.mcname(),
+            NAMESPACE,
+            value.mcname(),
+            NAMESPACE
+        )
+    }
     pub fn pure_assign_value(&self, value:i32) -> String {
         format!(
             "scoreboard players set {} {} {}",
@@ -224,9 +321,59 @@ impl Scoreboard {
             )
         )
     }
+    pub fn pure_match_range(&self, range:&str) -> String {
+        format!(
+            "score {} {} matches {}",
+            self.mcname(),
+            NAMESPACE,
+            range
+        )
+    }
+    /// The pure function to get a command to copy this scoreboard onto a path of data storage.
+    pub fn pure_store_to_storage(&self, storage:&str, path:&str) -> String {
+        if self.data_type == Types::Lng {
+            let (hi, lo) = long::halves(self);
+            return format!(
+                "{}\n{}",
+                hi.pure_store_to_storage(storage, &format!("{}.hi", path)),
+                lo.pure_store_to_storage(storage, &format!("{}.lo", path))
+            );
+        }
+        format!(
+            "execute store result storage {} {} int 1 run scoreboard players get {} {}",
+            storage,
+            path,
+            self.mcname(),
+            NAMESPACE
+        )
+    }
+    /// The pure function to get a command to copy a path of data storage onto this scoreboard.
+    pub fn pure_load_from_storage(&self, storage:&str, path:&str) -> String {
+        if self.data_type == Types::Lng {
+            let (hi, lo) = long::halves(self);
+            return format!(
+                "{}\n{}",
+                hi.pure_load_from_storage(storage, &format!("{}.hi", path)),
+                lo.pure_load_from_storage(storage, &format!("{}.lo", path))
+            );
+        }
+        self.pure_store_result(&format!("data get storage {} {}", storage, path))
+    }
+    /// The pure function to get a command to store the result of given command onto this scoreboard.
+    pub fn pure_store_result(&self, command:&str) -> String {
+        format!("execute store result score {} {} run {}", self.mcname(), NAMESPACE, command)
+    }
+    /// The pure function to get a JSON text component showing the value of this scoreboard.
+    pub fn pure_score_json(&self) -> String {
+        format!(
+            "{{\"score\":{{\"name\":\"{}\",\"objective\":\"{}\"}}}}",
+            self.mcname(),
+            NAMESPACE
+        )
+    }
     pub fn pure_compare_value_not_equal(&self, operator:&str, value:i32) -> (String, String) {
         let constant = Scoreboard::from(value);
-        (constant.pure_assign_value(value), constant.pure_compare_score(operator, &constant))
+        (String::new(), self.pure_compare_score(operator, &constant))
     }
 }
 
@@ -0,0 +1,106 @@
+use crate::compile_task::{
+    evaluater::EvaluateError,
+    scoreboard::{
+        float::{self, get_magnif}, long, Calcable, Scoreboard, Types
+    }
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// How a float is rounded onto an int.
+pub enum Rounding {
+    /// Rounds toward zero, used by `as int`.
+    Trunc,
+    Round,
+    Floor,
+    Ceil
+}
+impl Rounding {
+    pub fn from_name(name:&str) -> Option<Rounding> {
+        match name {
+            "round" => Some(Rounding::Round),
+            "floor" => Some(Rounding::Floor),
+            "ceil" => Some(Rounding::Ceil),
+            _ => None
+        }
+    }
+    fn apply(&self, value:f64) -> i32 {
+        match self {
+            Rounding::Trunc => value.trunc() as i32,
+            // The same as the runtime rounding, which rounds half up even if the value is negative.
+            Rounding::Round => (value + 0.5).floor() as i32,
+            Rounding::Floor => value.floor() as i32,
+            Rounding::Ceil => value.ceil() as i32
+        }
+    }
+}
+
+/// Convert a value onto the type of given scoreboard explicitly, then store it.
+pub fn cast(score:&Scoreboard, value:&Calcable, rounding:Rounding) -> Result<String, EvaluateError> {
+    let unsupported = || EvaluateError::CastOccuredBetweenUnsupportedTypes(value.get_type(), score.data_type.clone());
+    match (&score.data_type, value) {
+        (Types::Int, Calcable::Int(i)) => Ok(score.pure_assign_value(*i)),
+        (Types::Int, Calcable::Flt(f, _)) => Ok(score.pure_assign_value(rounding.apply(*f))),
+        (Types::Int, Calcable::Bln(b)) => Ok(score.pure_assign_value(*b as i32)),
+        (Types::Flt(p), Calcable::Int(i)) => Ok(score.pure_assign_value(float::scale_int(*i, *p)?)),
+        (Types::Flt(p), Calcable::Flt(f, _)) => Ok(score.pure_assign_value(float::scale_float(*f, *p)?)),
+        (Types::Flt(p), Calcable::Bln(b)) => Ok(score.pure_assign_value(*b as i32 * get_magnif(*p))),
+        (Types::Bln, Calcable::Int(i)) => Ok(score.pure_assign_value((*i != 0) as i32)),
+        (Types::Bln, Calcable::Flt(f, _)) => Ok(score.pure_assign_value((*f != 0.0) as i32)),
+        (Types::Bln, Calcable::Bln(b)) => Ok(score.pure_assign_value(*b as i32)),
+        // Narrowing a long wraps around as well as an overflow of a score.
+        (Types::Int, Calcable::Lng(l)) => Ok(score.pure_assign_value(*l as i32)),
+        (Types::Lng, Calcable::Int(_) | Calcable::Lng(_)) => score.assign(value),
+        (_, Calcable::Scr(s)) => match (&score.data_type, &s.data_type) {
+            (Types::Int, Types::Int | Types::Bln | Types::Enum(_)) | (Types::Bln, Types::Bln) => Ok(
+                score.pure_assign_score(s)
+            ),
+            // Floats of different precisions are rescaled by the assignment.
+            (Types::Flt(_), Types::Flt(_)) => score.assign(&Calcable::Scr(s)),
+            (Types::Int, Types::Flt(_)) => float_to_int(score, s, rounding),
+            (Types::Lng, Types::Int | Types::Lng) => score.assign(&Calcable::Scr(s)),
+            (Types::Int, Types::Lng) => long_to_int(score, s),
+            (Types::Flt(p), Types::Int | Types::Bln) => Ok(
+                format!("{}\n{}", score.pure_assign_score(s), score.pure_calc_num("*", get_magnif(*p))?)
+            ),
+            (Types::Bln, Types::Int | Types::Flt(_)) => Ok(
+                format!(
+                    "{}\nexecute unless {} run {}",
+                    score.pure_assign_value(0),
+                    s.pure_compare_value("==", 0)?,
+                    score.pure_assign_value(1)
+                )
+            ),
+            _ => Err(unsupported())
+        },
+        (_, Calcable::Mcf(f)) => cast(score, &Calcable::Scr(&f.ret_container), rounding),
+        _ => Err(unsupported())
+    }
+}
+/// Scoreboard division of Minecraft is a floor division,
+/// so the dividend is shifted before dividing to get the other roundings.
+fn float_to_int(score:&Scoreboard, source:&Scoreboard, rounding:Rounding) -> Result<String, EvaluateError> {
+    let magnif = get_magnif(float::precision_of(source));
+    let mut res:Vec<String> = vec![score.pure_assign_score(source)];
+    match rounding {
+        Rounding::Floor => (),
+        Rounding::Ceil => res.push(score.pure_calc_num("+", magnif - 1)?),
+        Rounding::Round => res.push(score.pure_calc_num("+", magnif / 2)?),
+        Rounding::Trunc => res.push(
+            format!("execute if {} run {}", score.pure_compare_value("<=", -1)?, score.pure_calc_num("+", magnif - 1)?)
+        )
+    }
+    res.push(score.pure_calc_num("/", magnif)?);
+    Ok(res.join("\n"))
+}
+/// The high half is multiplied by the base, so the result wraps around if it doesn't fit in a score.
+fn long_to_int(score:&Scoreboard, source:&Scoreboard) -> Result<String, EvaluateError> {
+    let (hi, lo) = long::halves(source);
+    Ok(
+        format!(
+            "{}\n{}\n{}",
+            score.pure_assign_score(&hi),
+            score.pure_calc_num("*", long::BASE)?,
+            score.pure_calc_score("+", &lo)
+        )
+    )
+}
@@ -0,0 +1,29 @@
+use crate::compile_task::evaluater::EvaluateError;
+
+use super::{Calcable, Scoreboard};
+
+pub fn assign(score:&Scoreboard, value:&Calcable) -> Result<String, EvaluateError> {
+    match value {
+        Calcable::Enm(_, i) if value.get_type() == score.data_type => Ok(
+            score.pure_assign_value(*i)
+        ),
+        Calcable::Scr(s) if s.data_type == score.data_type => Ok(
+            score.pure_assign_score(s)
+        ),
+        Calcable::Mcf(f) => assign(
+            score,
+            &Calcable::Scr(&f.ret_container)
+        ),
+        _ => Err(EvaluateError::AssignOccuredBetweenUnsupportedTypes(value.get_type(), score.data_type.clone()))
+    }
+}
+pub fn compare(score:&Scoreboard, operator:&str, value:&Calcable) -> Result<String, EvaluateError> {
+    match operator {
+        "==" => match value {
+            Calcable::Enm(_, i) if value.get_type() == score.data_type => score.pure_compare_value(operator, *i),
+            Calcable::Scr(s) if s.data_type == score.data_type => Ok(score.pure_compare_score("=", s)),
+            _ => Err(EvaluateError::ComparementOccuredBetweenUnsupportedTypes(score.data_type.clone(), value.get_type()))
+        },
+        _ => Err(EvaluateError::UnknownOperatorGiven(operator.to_string()))
+    }
+}
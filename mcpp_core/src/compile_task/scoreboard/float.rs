@@ -1,82 +1,190 @@
 use crate::compile_task::{
     evaluater::EvaluateError,
     scoreboard::{
-        float, Calcable, Scoreboard, Types
+        overflow::{self, Overflow}, Calcable, Scoreboard, Types
     }
 };
 use super::get_temp_score;
-pub const ACCURATION:u32 = 3;
+/// Digits under the decimal point of a float, used when no precision is annotated.
+pub const DEFAULT_PRECISION:u32 = 3;
+/// The most digits of a precision, because 10^10 overflows a score.
+pub const MAX_PRECISION:u32 = 9;
 
-pub fn get_magnif() -> i32 {
-    10_i32.pow(ACCURATION)
+pub fn get_magnif(precision:u32) -> i32 {
+    10_i32.pow(precision)
 }
-pub fn scale_float(input:f32) -> i32 {
-    (input * (10_i32.pow(float::ACCURATION) as f32)) as i32
+/// Scale a float literal by given precision, rounded to the nearest.
+/// 
+/// A literal which doesn't fit in a score after scaling is an error, instead of being clamped.
+pub fn scale_float(input:f64, precision:u32) -> Result<i32, EvaluateError> {
+    let scaled = (input * get_magnif(precision) as f64).round();
+    if (i32::MIN as f64..=i32::MAX as f64).contains(&scaled) {
+        Ok(scaled as i32)
+    } else {
+        Err(EvaluateError::LiteralOutOfRange(input.to_string(), Types::Flt(precision)))
+    }
+}
+/// Scale an int literal by given precision.
+pub fn scale_int(input:i32, precision:u32) -> Result<i32, EvaluateError> {
+    input
+        .checked_mul(get_magnif(precision))
+        .ok_or(EvaluateError::LiteralOutOfRange(input.to_string(), Types::Flt(precision)))
+}
+/// The pure function to get the precision of a float scoreboard.
+pub fn precision_of(score:&Scoreboard) -> u32 {
+    match score.data_type {
+        Types::Flt(p) => p,
+        _ => DEFAULT_PRECISION
+    }
+}
+/// The pure function to get commands to convert a float scoreboard between precisions in place.
+pub fn rescale(score:&Scoreboard, from:u32, to:u32) -> Result<Vec<String>, EvaluateError> {
+    Ok(
+        if from < to {
+            vec![score.pure_calc_num("*", get_magnif(to - from))?]
+        } else if from > to {
+            vec![score.pure_calc_num("/", get_magnif(from - to))?]
+        } else {
+            Vec::new()
+        }
+    )
 }
 
-pub fn calc(score:&Scoreboard, operator:&str, target:&Calcable) -> Result<String, EvaluateError> {
+pub fn calc(score:&Scoreboard, operator:&str, target:&Calcable, overflow:Overflow) -> Result<String, EvaluateError> {
     match target {
-        Calcable::Int(i) => calc_num(score, operator, *i),
-        Calcable::Flt(f) => calc_float(score, operator, *f),
-        Calcable::Scr(s) => calc_score(score, operator, s),
-        Calcable::Mcf(f) => calc(score, operator, &Calcable::Scr(&f.ret_container)),
+        Calcable::Int(i) => calc_num(score, operator, *i, overflow),
+        Calcable::Flt(f, _) => calc_float(score, operator, *f, overflow),
+        Calcable::Scr(s) => calc_score(score, operator, s, overflow),
+        Calcable::Mcf(f) => calc(score, operator, &Calcable::Scr(&f.ret_container), overflow),
         _ => Err(EvaluateError::OperationOccuredBetweenUnsupportedTypes(score.data_type.clone(), target.get_type()))
     }
 }
-fn calc_num(score:&Scoreboard, operator:&str, num:i32) -> Result<String, EvaluateError> {
-    match operator {
-        "+" | "-" => score.pure_calc_num(operator, num * get_magnif()),
-        _ => score.pure_calc_num(operator, num)
-    }
+fn calc_num(score:&Scoreboard, operator:&str, num:i32, overflow:Overflow) -> Result<String, EvaluateError> {
+    // The remainder has the fraction of score, so a modulo by an int is scaled as well as adding it.
+    let scaled = match operator {
+        "+" | "-" | "%" => scale_int(num, precision_of(score))?,
+        _ => num
+    };
+    Ok(overflow::calc_num(score, operator, scaled, overflow)?.join("\n"))
 }
-fn calc_float(score:&Scoreboard, operator:&str, num:f32) -> Result<String, EvaluateError> {
+fn calc_float(score:&Scoreboard, operator:&str, num:f64, overflow:Overflow) -> Result<String, EvaluateError> {
+    let precision = precision_of(score);
     match operator {
-        "+" | "-" => score.pure_calc_num(operator, scale_float(num)),
-        "*" => Ok(
-            format!(
-                "{}\n{}",
-                score.pure_calc_num(operator, scale_float(num))?,
-                score.pure_calc_num("/", get_magnif().pow(2))?
-            )
-        ),
-        "/" => Ok(
-            format!(
-                "{}\n{}",
-                score.pure_calc_num("*", get_magnif())?,
-                score.pure_calc_num("/", scale_float(num))?
-            )
-        ),
-        _ => Err(EvaluateError::OperationOccuredBetweenUnsupportedTypes(score.data_type.clone(), Types::Flt))
+        "+" | "-" => Ok(overflow::calc_num(score, operator, scale_float(num, precision)?, overflow)?.join("\n")),
+        "*" | "/" | "%" => {
+            let source = work_score("b");
+            let mut commands = vec![source.pure_assign_value(scale_float(num, precision)?)];
+            commands.append(&mut calc_scaled(score, operator, &source, overflow)?);
+            Ok(commands.join("\n"))
+        },
+        _ => Err(EvaluateError::OperationOccuredBetweenUnsupportedTypes(score.data_type.clone(), Types::Flt(precision)))
     }
 }
-fn calc_score(score:&Scoreboard, operator:&str, source:&Scoreboard) -> Result<String, EvaluateError> {
+fn calc_score(score:&Scoreboard, operator:&str, source:&Scoreboard, overflow:Overflow) -> Result<String, EvaluateError> {
+    let precision = precision_of(score);
     match source.data_type {
+        // An int is not scaled, so multiplying and dividing by it need no rescale.
+        Types::Int if operator == "*" || operator == "/" => Ok(
+            overflow::calc_score(score, operator, source, overflow)?.join("\n")
+        ),
         Types::Int => {
             let temp_score = super::get_temp_score();
             Ok(
                 format!(
                     "{}\n{}\n{}",
                     temp_score.assign(&Calcable::Scr(source))?,
-                    temp_score.calc("*", &Calcable::Int(get_magnif()))?,
-                    score.pure_calc_score(operator, &temp_score)
+                    temp_score.calc("*", &Calcable::Int(get_magnif(precision)))?,
+                    overflow::calc_score(score, operator, &temp_score, overflow)?.join("\n")
                 )
             )
         },
-        Types::Flt => Ok(
-            source.pure_calc_score(operator, source)
-        ),
+        // The source is copied at first, so it is never modified by rescaling and even it can be the same as score.
+        Types::Flt(p) => {
+            let copied = work_score("b");
+            let mut commands = vec![copied.pure_assign_score(source)];
+            commands.append(&mut rescale(&copied, p, precision)?);
+            commands.append(&mut calc_scaled(score, operator, &copied, overflow)?);
+            Ok(commands.join("\n"))
+        },
         _ => Err(
             EvaluateError::OperationOccuredBetweenUnsupportedTypes(score.data_type.clone(), source.data_type.clone())
         )
     }
 }
+/// Get a temporary scoreboard used in a calculation between two floats.
+fn work_score(name:&str) -> Scoreboard {
+    Scoreboard {
+        name : name.to_string(),
+        data_type : Types::Int,
+        scope : vec!["Calc".to_string(), "FIXED".to_string()]
+    }
+}
+/// The pure function to get commands to calc two floats of the same precision, which are scaled by M.
+/// 
+/// Scoreboard operations of Minecraft use floor division and floor modulo,
+/// so each step below is exact and the result is floored as well as a division of int.
+/// 
+/// Each step which accumulates onto score is guarded by given mode, because an overflow of any of them breaks the result.
+fn calc_scaled(score:&Scoreboard, operator:&str, source:&Scoreboard, overflow:Overflow) -> Result<Vec<String>, EvaluateError> {
+    let precision = precision_of(score);
+    let magnif = get_magnif(precision);
+    match operator {
+        // A*B/M overflows soon if A*B is calculated first, even when the result fits.
+        // So both are divided by M first, then A*B/M = Ah*Bh*M + Ah*Bl + Al*Bh + Al*Bl/M.
+        "*" => {
+            let (ah, al, bh, bl, term) = (work_score("ah"), work_score("al"), work_score("bh"), work_score("bl"), work_score("term"));
+            let mut commands:Vec<String> = Vec::new();
+            for (high, low, of) in [(&ah, &al, score), (&bh, &bl, source)] {
+                commands.push(high.pure_assign_score(of));
+                commands.push(high.pure_calc_num("/", magnif)?);
+                commands.push(low.pure_assign_score(of));
+                commands.push(low.pure_calc_num("%", magnif)?);
+            }
+            commands.push(score.pure_assign_score(&ah));
+            commands.append(&mut overflow::calc_score(score, "*", &bh, overflow)?);
+            commands.append(&mut overflow::calc_num(score, "*", magnif, overflow)?);
+            for (left, right, divide) in [(&ah, &bl, false), (&al, &bh, false), (&al, &bl, true)] {
+                commands.push(term.pure_assign_score(left));
+                commands.push(term.pure_calc_score("*", right));
+                if divide {
+                    commands.push(term.pure_calc_num("/", magnif)?);
+                }
+                commands.append(&mut overflow::calc_score(score, "+", &term, overflow)?);
+            }
+            Ok(commands)
+        },
+        // A*M/B overflows soon if A*M is calculated first.
+        // So A/B is calculated at first, then each digits under the decimal point is calculated by a long division.
+        "/" => {
+            let (remainder, digit) = (work_score("remainder"), work_score("digit"));
+            let mut commands = vec![
+                remainder.pure_assign_score(score),
+                remainder.pure_calc_score("%", source)
+            ];
+            commands.append(&mut overflow::calc_score(score, "/", source, overflow)?);
+            commands.append(&mut overflow::calc_num(score, "*", magnif, overflow)?);
+            for place in (0..precision).rev().map(get_magnif) {
+                commands.push(remainder.pure_calc_num("*", 10)?);
+                commands.push(digit.pure_assign_score(&remainder));
+                commands.push(digit.pure_calc_score("/", source));
+                commands.push(remainder.pure_calc_score("%", source));
+                if place != 1 {
+                    commands.push(digit.pure_calc_num("*", place)?);
+                }
+                commands.append(&mut overflow::calc_score(score, "+", &digit, overflow)?);
+            }
+            Ok(commands)
+        },
+        _ => overflow::calc_score(score, operator, source, overflow)
+    }
+}
 pub fn assign(score:&Scoreboard, value:&Calcable) -> Result<String, EvaluateError> {
     match value {
         Calcable::Int(i) => Ok(
-            score.pure_assign_value(i * get_magnif())
+            score.pure_assign_value(scale_int(*i, precision_of(score))?)
         ),
-        Calcable::Flt(f) => Ok(
-            score.pure_assign_value(scale_float(*f))
+        Calcable::Flt(f, _) => Ok(
+            score.pure_assign_value(scale_float(*f, precision_of(score))?)
         ),
         Calcable::Scr(s) => match s.data_type {
             Types::Int => {
@@ -85,13 +193,17 @@ pub fn assign(score:&Scoreboard, value:&Calcable) -> Result<String, EvaluateErro
                     format!(
                         "{}\n{}\n{}",
                         temp_score.assign(&Calcable::Scr(s))?,
-                        temp_score.calc("*", &Calcable::Int(get_magnif()))?,
+                        temp_score.calc("*", &Calcable::Int(get_magnif(precision_of(score))))?,
                         score.pure_assign_score(&temp_score)
                     )
                 )
             },
-            Types::Flt => Ok(score.pure_assign_score(s)),
-            _ => Err(EvaluateError::AssignOccuredBetweenUnsupportedTypes(Types::Flt, value.get_type()))
+            Types::Flt(p) => {
+                let mut commands = vec![score.pure_assign_score(s)];
+                commands.append(&mut rescale(score, p, precision_of(score))?);
+                Ok(commands.join("\n"))
+            },
+            _ => Err(EvaluateError::AssignOccuredBetweenUnsupportedTypes(score.data_type.clone(), value.get_type()))
         },
         Calcable::Mcf(f) => assign(
             score,
@@ -102,31 +214,37 @@ pub fn assign(score:&Scoreboard, value:&Calcable) -> Result<String, EvaluateErro
 }
 // Returning a tuple that's constructed by a preoperation and a comparement
 pub fn compare(score:&Scoreboard, operator:&str, value:&Calcable) -> Result<(String, String), EvaluateError> {
+    let precision = precision_of(score);
     match operator {
         "<" | "<=" | "==" | ">=" | ">" => match value {
             Calcable::Int(i) => {
                 Ok(
                     match operator {
-                        "<" | ">" => score.pure_compare_value_not_equal(operator, i * get_magnif()),
-                        _ => (String::new(), score.pure_compare_value(operator, i * get_magnif())?)
+                        "<" | ">" => score.pure_compare_value_not_equal(operator, scale_int(*i, precision)?),
+                        _ => (String::new(), score.pure_compare_value(operator, scale_int(*i, precision)?)?)
                     }
                 )
             },
-            Calcable::Flt(f) => Ok(
-                (String::new(), score.pure_compare_value(operator, float::scale_float(*f))?)
+            Calcable::Flt(f, _) => Ok(
+                (String::new(), score.pure_compare_value(operator, scale_float(*f, precision)?)?)
             ),
             Calcable::Scr(s) => match s.data_type {
-                Types::Flt => Ok((String::new(), score.pure_compare_score(operator, s))),
+                Types::Flt(p) if p != precision => {
+                    let temp_score = get_temp_score();
+                    let mut preoperation = vec![temp_score.pure_assign_score(s)];
+                    preoperation.append(&mut rescale(&temp_score, p, precision)?);
+                    Ok((preoperation.join("\n"), score.pure_compare_score(operator, &temp_score)))
+                },
+                Types::Flt(_) => Ok((String::new(), score.pure_compare_score(operator, s))),
                 Types::Int => {
                     let temp_score = get_temp_score();
                     Ok((
-                        String::new(),
                         format!(
-                            "{}\n{}\n{}",
+                            "{}\n{}",
                             temp_score.pure_assign_score(s),
-                            temp_score.pure_calc_num("*", get_magnif())?,
-                            s.pure_compare_score(operator, &temp_score)
-                        )
+                            temp_score.pure_calc_num("*", get_magnif(precision))?
+                        ),
+                        score.pure_compare_score(operator, &temp_score)
                     ))
                 },
                 _ => Err(EvaluateError::ComparementOccuredBetweenUnsupportedTypes(score.data_type.clone(), value.get_type()))
@@ -1,39 +1,39 @@
 use crate::compile_task::{
     evaluater::EvaluateError,
     scoreboard::{
-        float::{self, get_magnif}, Calcable, Scoreboard, Types
+        float::{self, get_magnif}, overflow::{self, Overflow}, Calcable, Scoreboard, Types
     }
 };
 use super::get_temp_score;
 
-pub fn calc(score:&Scoreboard, operator:&str, target:&Calcable) -> Result<String, EvaluateError> {
+pub fn calc(score:&Scoreboard, operator:&str, target:&Calcable, overflow:Overflow) -> Result<String, EvaluateError> {
     match target {
-        Calcable::Int(i) => calc_num(score, operator, *i),
-        Calcable::Flt(f) => calc_float(score, operator, *f),
-        Calcable::Scr(s) => calc_score(score, operator, s),
-        Calcable::Mcf(f) => calc(score, operator, &Calcable::Scr(&f.ret_container)),
+        Calcable::Int(i) => calc_num(score, operator, *i, overflow),
+        Calcable::Flt(f, p) => calc_float(score, operator, *f, *p, overflow),
+        Calcable::Scr(s) => calc_score(score, operator, s, overflow),
+        Calcable::Mcf(f) => calc(score, operator, &Calcable::Scr(&f.ret_container), overflow),
         _ => Err(EvaluateError::OperationOccuredBetweenUnsupportedTypes(score.data_type.clone(), target.get_type()))
     }
 }
 /// The pure function to get corresponding scoreboard command.
-fn calc_num(score:&Scoreboard, operator:&str, num:i32) -> Result<String, EvaluateError> {
-    score.pure_calc_num(operator, num)
+fn calc_num(score:&Scoreboard, operator:&str, num:i32, overflow:Overflow) -> Result<String, EvaluateError> {
+    Ok(overflow::calc_num(score, operator, num, overflow)?.join("\n"))
 }
-fn calc_float(score:&Scoreboard, operator:&str, source:f32) -> Result<String, EvaluateError> {
-    score.pure_calc_num(operator, float::scale_float(source))
+fn calc_float(score:&Scoreboard, operator:&str, source:f64, precision:u32, overflow:Overflow) -> Result<String, EvaluateError> {
+    calc_num(score, operator, float::scale_float(source, precision)?, overflow)
 }
-fn calc_score(score:&Scoreboard, operator:&str, source:&Scoreboard) -> Result<String, EvaluateError> {
+fn calc_score(score:&Scoreboard, operator:&str, source:&Scoreboard, overflow:Overflow) -> Result<String, EvaluateError> {
     match source.data_type {
         Types::Int => Ok(
-            score.pure_calc_score(operator, source)
+            overflow::calc_score(score, operator, source, overflow)?.join("\n")
         ),
-        Types::Flt => {
+        Types::Flt(_) => {
             let temp_score = get_temp_score();
             Ok(
                 format!(
                     "{}\n{}",
                     temp_score.assign(&Calcable::Scr(source))?,
-                    calc_score(score, operator, &temp_score)?
+                    calc_score(score, operator, &temp_score, overflow)?
                 )
             )
         },
@@ -52,13 +52,17 @@ pub fn assign(scoreboard:&Scoreboard, value:&Calcable) -> Result<String, Evaluat
                 Types::Int => Ok(
                     scoreboard.pure_assign_score(s)
                 ),
-                Types::Flt => {
+                Types::Flt(_) => {
+                    println!(
+                        "⚠️  {} is narrowed from float onto int implicitly. Use as int, round(), floor() or ceil() to make it explicit.",
+                        s
+                    );
                     let temp_score = get_temp_score();
                     Ok(
                         format!(
                             "{}\n{}\n{}",
-                            temp_score.assign(&Calcable::Scr(s))?,
-                            temp_score.calc("/", &Calcable::Int(get_magnif()))?,
+                            temp_score.pure_assign_score(s),
+                            temp_score.calc("/", &Calcable::Int(get_magnif(float::precision_of(s))))?,
                             scoreboard.assign(&Calcable::Scr(&temp_score))?
                         )
                     )
@@ -68,9 +72,14 @@ pub fn assign(scoreboard:&Scoreboard, value:&Calcable) -> Result<String, Evaluat
                 )
             }
         }
-        &Calcable::Flt(f) => scoreboard.assign(
-            &Calcable::Int(float::scale_float(f))
-        ),
+        &Calcable::Flt(f, _) => {
+            if f.fract() != 0.0 {
+                println!("⚠️  {} is narrowed onto int implicitly, so the fractional part is lost.", f);
+            }
+            scoreboard.assign(
+                &Calcable::Int(f as i32)
+            )
+        },
         &Calcable::Mcf(f) => scoreboard.assign(
             &Calcable::Scr(&f.ret_container)
         ),
@@ -88,21 +97,21 @@ pub fn compare(score:&Scoreboard, operator:&str, value:&Calcable) -> Result<(Str
                     _ => (String::new(), score.pure_compare_value(operator, *i)?)
                 }
             ),
-            Calcable::Flt(f) => Ok(
-                (String::new(), score.pure_compare_value(operator, float::scale_float(*f))?)
+            Calcable::Flt(f, p) => Ok(
+                (String::new(), score.pure_compare_value(operator, float::scale_float(*f, *p)?)?)
             ),
             Calcable::Scr(s) => match s.data_type {
                 Types::Int => Ok((
                     String::new(),
                     s.pure_compare_score(operator, s)
                 )),
-                Types::Flt => {
+                Types::Flt(_) => {
                     let temp_score = get_temp_score();
                     Ok((
                         format!(
                             "{}\n{}",
                             temp_score.pure_assign_score(s),
-                            temp_score.pure_calc_num("/", float::get_magnif())?,
+                            temp_score.pure_calc_num("/", float::get_magnif(float::precision_of(s)))?,
                         ),
                         score.pure_compare_score(operator, &temp_score)
                     ))
@@ -0,0 +1,266 @@
+use crate::compile_task::{
+    evaluater::EvaluateError,
+    scoreboard::{Calcable, Scoreboard, Types}
+};
+
+/// A long is stored as hi * BASE + lo, where lo is in 0..BASE.
+///
+/// Each half keeps 9 digits, so a long is printed as a decimal by printing the halves in order.
+pub const BASE:i32 = 1_000_000_000;
+/// The least and the greatest long, which are about ±2.1e18 because hi is a score, not the range of 64-bit integers.
+pub const MIN:i64 = i32::MIN as i64 * BASE as i64;
+pub const MAX:i64 = (i32::MAX as i64 + 1) * BASE as i64 - 1;
+/// Multiplication is done on limbs of 3 digits, so a product of two limbs never overflows.
+const LIMB:i32 = 1000;
+const LIMBS:usize = 6;
+
+#[test]
+fn long_test() {
+    let x = Scoreboard {
+        name : "x".to_string(),
+        data_type : Types::Lng,
+        scope : vec!["TEST".to_string()]
+    };
+    let assigned = assign(&x, &Calcable::Lng(-1_500_000_000)).unwrap();
+    println!("{}", assigned);
+    assert_eq!(
+        assigned,
+        "scoreboard players set #TEST.x.hi MCPP.var -2\nscoreboard players set #TEST.x.lo MCPP.var 500000000"
+    );
+    let added = calc(&x, "+", &Calcable::Int(1)).unwrap();
+    println!("{}", added);
+    assert!(added.contains("scoreboard players operation #TEST.x.hi MCPP.var += #Calc.LONG.carry MCPP.var"));
+    let multiplied = calc(&x, "*", &Calcable::Int(3)).unwrap();
+    println!("{}", multiplied);
+    assert!(multiplied.contains("scoreboard players operation #Calc.LONG.term MCPP.var *= #Calc.LONG.b5 MCPP.var"));
+    assert!(calc(&x, "/", &Calcable::Int(3)).is_err());
+    let (preop, condition) = compare(&x, ">=", &Calcable::Int(0)).unwrap();
+    println!("{}\n{}", preop, condition);
+    assert_eq!(condition, "score #Calc.LONG.compared MCPP.var matches 1");
+    assert!(assign(&x, &Calcable::Lng(MAX + 1)).is_err());
+    let absolute = abs(&x, &x).unwrap().join("\n");
+    println!("{}", absolute);
+    assert!(absolute.contains("execute if score #TEST.x.hi MCPP.var matches ..-1 run scoreboard players operation #TEST.x.hi MCPP.var = #Calc.LONG.negated.hi MCPP.var"));
+}
+
+/// The pure function to get the scoreboards of the high and the low half.
+///
+/// They are scoped under the name of the long, so `x:long` will be #x.hi and #x.lo in Minecraft.
+pub fn halves(score:&Scoreboard) -> (Scoreboard, Scoreboard) {
+    let mut scope = score.scope.clone();
+    scope.push(score.name.clone());
+    let half = |name:&str| Scoreboard {
+        name : name.to_string(),
+        data_type : Types::Int,
+        scope : scope.clone()
+    };
+    (half("hi"), half("lo"))
+}
+fn split_literal(value:i64) -> Result<(i32, i32), EvaluateError> {
+    match i32::try_from(value.div_euclid(BASE as i64)) {
+        Ok(hi) => Ok((hi, value.rem_euclid(BASE as i64) as i32)),
+        Err(_) => Err(EvaluateError::LiteralOutOfRange(value.to_string(), Types::Lng))
+    }
+}
+fn work_score(name:&str, data_type:Types) -> Scoreboard {
+    Scoreboard {
+        name : name.to_string(),
+        data_type,
+        scope : vec!["Calc".to_string(), "LONG".to_string()]
+    }
+}
+/// The pure function to get commands to carry the overflow of the low half onto the high half.
+///
+/// Both divisions of Minecraft are floor divisions, so a borrow is carried as a negative number.
+fn normalize(hi:&Scoreboard, lo:&Scoreboard) -> Result<Vec<String>, EvaluateError> {
+    let carry = work_score("carry", Types::Int);
+    Ok(vec![
+        carry.pure_assign_score(lo),
+        carry.pure_calc_num("/", BASE)?,
+        hi.pure_calc_score("+", &carry),
+        lo.pure_calc_num("%", BASE)?
+    ])
+}
+/// Convert a value onto a long, so every operation is done between two longs.
+fn to_long(value:&Calcable, name:&str) -> Result<(Vec<String>, Scoreboard), EvaluateError> {
+    match value {
+        Calcable::Scr(s) if s.data_type == Types::Lng => Ok((Vec::new(), (*s).clone())),
+        Calcable::Mcf(f) => to_long(&Calcable::Scr(&f.ret_container), name),
+        _ => {
+            let source = work_score(name, Types::Lng);
+            Ok((vec![assign(&source, value)?], source))
+        }
+    }
+}
+
+pub fn assign(score:&Scoreboard, value:&Calcable) -> Result<String, EvaluateError> {
+    let (hi, lo) = halves(score);
+    match value {
+        Calcable::Int(i) => assign(score, &Calcable::Lng(*i as i64)),
+        Calcable::Lng(i) => {
+            let (h, l) = split_literal(*i)?;
+            Ok(format!("{}\n{}", hi.pure_assign_value(h), lo.pure_assign_value(l)))
+        },
+        Calcable::Scr(s) => match s.data_type {
+            Types::Int => {
+                let mut res = vec![lo.pure_assign_score(s), hi.pure_assign_value(0)];
+                res.append(&mut normalize(&hi, &lo)?);
+                Ok(res.join("\n"))
+            },
+            Types::Lng => {
+                let (shi, slo) = halves(s);
+                Ok(format!("{}\n{}", hi.pure_assign_score(&shi), lo.pure_assign_score(&slo)))
+            },
+            _ => Err(EvaluateError::AssignOccuredBetweenUnsupportedTypes(value.get_type(), Types::Lng))
+        },
+        Calcable::Mcf(f) => assign(score, &Calcable::Scr(&f.ret_container)),
+        _ => Err(EvaluateError::AssignOccuredBetweenUnsupportedTypes(value.get_type(), Types::Lng))
+    }
+}
+pub fn calc(score:&Scoreboard, operator:&str, value:&Calcable) -> Result<String, EvaluateError> {
+    if !matches!(value.get_type(), Types::Int | Types::Lng) {
+        return Err(EvaluateError::OperationOccuredBetweenUnsupportedTypes(Types::Lng, value.get_type()));
+    }
+    match operator {
+        "+" | "-" => {
+            let (mut res, source) = to_long(value, "source")?;
+            let (hi, lo) = halves(score);
+            let (shi, slo) = halves(&source);
+            res.push(lo.pure_calc_score(operator, &slo));
+            res.push(hi.pure_calc_score(operator, &shi));
+            res.append(&mut normalize(&hi, &lo)?);
+            Ok(res.join("\n"))
+        },
+        "*" => multiply(score, value),
+        _ => Err(EvaluateError::OperationOccuredBetweenUnsupportedTypes(Types::Lng, value.get_type()))
+    }
+}
+/// The pure function to get commands to split a long onto limbs of 3 digits.
+///
+/// Only the most significant limb keeps the sign, the others are in 0..LIMB.
+fn split_limbs(score:&Scoreboard, prefix:&str) -> Result<(Vec<String>, Vec<Scoreboard>), EvaluateError> {
+    let (hi, lo) = halves(score);
+    let limbs:Vec<Scoreboard> = (0..LIMBS)
+        .map(|k| work_score(&format!("{}{}", prefix, k), Types::Int))
+        .collect();
+    let mut res = Vec::new();
+    for (k, limb) in limbs.iter().enumerate() {
+        let half = if k < LIMBS / 2 { &lo } else { &hi };
+        let shift = LIMB.pow((k % (LIMBS / 2)) as u32);
+        res.push(limb.pure_assign_score(half));
+        if shift != 1 {
+            res.push(limb.pure_calc_num("/", shift)?);
+        }
+        if k != LIMBS - 1 {
+            res.push(limb.pure_calc_num("%", LIMB)?);
+        }
+    }
+    Ok((res, limbs))
+}
+/// Multiply by the schoolbook method on limbs.
+///
+/// Products above the sixth limb are folded onto it with wrapping multipliers,
+/// so the high half is still right after it wraps around as a 32-bit score.
+fn multiply(score:&Scoreboard, value:&Calcable) -> Result<String, EvaluateError> {
+    let (mut res, source) = to_long(value, "source")?;
+    let (mut a_commands, a) = split_limbs(score, "a")?;
+    let (mut b_commands, b) = split_limbs(&source, "b")?;
+    res.append(&mut a_commands);
+    res.append(&mut b_commands);
+
+    let products:Vec<Scoreboard> = (0..LIMBS)
+        .map(|k| work_score(&format!("c{}", k), Types::Int))
+        .collect();
+    let term = work_score("term", Types::Int);
+    for k in 0..LIMBS {
+        res.push(products[k].pure_assign_value(0));
+        for i in 0..=k {
+            res.push(term.pure_assign_score(&a[i]));
+            res.push(term.pure_calc_score("*", &b[k - i]));
+            res.push(products[k].pure_calc_score("+", &term));
+        }
+    }
+    for (i, j) in (1..LIMBS).flat_map(|i| (LIMBS - i..LIMBS).map(move |j| (i, j))) {
+        res.push(term.pure_assign_score(&a[i]));
+        res.push(term.pure_calc_score("*", &b[j]));
+        res.push(term.pure_calc_num("*", LIMB.wrapping_pow((i + j + 1 - LIMBS) as u32))?);
+        res.push(products[LIMBS - 1].pure_calc_score("+", &term));
+    }
+    let carry = work_score("carry", Types::Int);
+    for k in 0..LIMBS - 1 {
+        res.push(carry.pure_assign_score(&products[k]));
+        res.push(carry.pure_calc_num("/", LIMB)?);
+        res.push(products[k + 1].pure_calc_score("+", &carry));
+        res.push(products[k].pure_calc_num("%", LIMB)?);
+    }
+    // Pack the limbs back onto the halves.
+    let (hi, lo) = halves(score);
+    for (half, limbs) in [(&lo, &products[..LIMBS / 2]), (&hi, &products[LIMBS / 2..])] {
+        res.push(half.pure_assign_score(&limbs[0]));
+        for (k, limb) in limbs.iter().enumerate().skip(1) {
+            res.push(term.pure_assign_score(limb));
+            res.push(term.pure_calc_num("*", LIMB.pow(k as u32))?);
+            res.push(half.pure_calc_score("+", &term));
+        }
+    }
+    Ok(res.join("\n"))
+}
+/// Compare by the high halves first, then by the low halves if the high halves are the same.
+///
+/// The result is stored onto #Calc.LONG.compared, because it can't be done by one condition.
+pub fn compare(score:&Scoreboard, operator:&str, value:&Calcable) -> Result<(String, String), EvaluateError> {
+    if !matches!(value.get_type(), Types::Int | Types::Lng) {
+        return Err(EvaluateError::ComparementOccuredBetweenUnsupportedTypes(Types::Lng, value.get_type()));
+    }
+    let (mut res, source) = to_long(value, "source")?;
+    let result = work_score("compared", Types::Bln);
+    let (hi, lo) = halves(score);
+    let (shi, slo) = halves(&source);
+    res.push(result.pure_assign_value(0));
+    match operator {
+        "==" => res.push(
+            format!(
+                "execute if {} if {} run {}",
+                hi.pure_compare_score("=", &shi),
+                lo.pure_compare_score("=", &slo),
+                result.pure_assign_value(1)
+            )
+        ),
+        "<" | "<=" | ">" | ">=" => {
+            let strict = &operator[..1];
+            res.push(format!("execute if {} run {}", hi.pure_compare_score(strict, &shi), result.pure_assign_value(1)));
+            res.push(
+                format!(
+                    "execute if {} if {} run {}",
+                    hi.pure_compare_score("=", &shi),
+                    lo.pure_compare_score(operator, &slo),
+                    result.pure_assign_value(1)
+                )
+            );
+        },
+        _ => return Err(EvaluateError::UnknownOperatorGiven(operator.to_string()))
+    }
+    Ok((res.join("\n"), result.pure_compare_value("==", 1)?))
+}
+/// The pure function to get commands to store the absolute value of a long onto another long.
+///
+/// The high half is negative only if the long is negative, because the low half is always in \[0, BASE).
+pub fn abs(score:&Scoreboard, into:&Scoreboard) -> Result<Vec<String>, EvaluateError> {
+    let (hi, lo) = halves(score);
+    let (abs_hi, abs_lo) = halves(into);
+    let (negated_hi, negated_lo) = halves(&work_score("negated", Types::Lng));
+    let negative = hi.pure_match_range("..-1");
+
+    let mut res = vec![
+        abs_hi.pure_assign_score(&hi),
+        abs_lo.pure_assign_score(&lo),
+        negated_hi.pure_assign_value(0),
+        negated_hi.pure_calc_score("-", &hi),
+        negated_lo.pure_assign_value(0),
+        negated_lo.pure_calc_score("-", &lo)
+    ];
+    res.append(&mut normalize(&negated_hi, &negated_lo)?);
+    res.push(format!("execute if {} run {}", negative, abs_hi.pure_assign_score(&negated_hi)));
+    res.push(format!("execute if {} run {}", negative, abs_lo.pure_assign_score(&negated_lo)));
+    Ok(res)
+}
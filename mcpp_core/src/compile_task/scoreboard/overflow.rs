@@ -0,0 +1,163 @@
+//! How arithmetic behaves when it overflows.
+//!
+//! Arithmetic of int and float wraps around as scoreboards of Minecraft do by default.
+//!
+//! It can be changed for the whole project by the build option, or for a sentence and the inside of it
+//! by `#[overflow(checked)]` or `#[overflow(saturating)]` put before the sentence.
+//! Checked arithmetic raises a runtime error then leaves the function, and saturating arithmetic clamps the result at the bounds of a score.
+
+use std::fmt;
+
+use crate::compile_task::{
+    evaluater::EvaluateError,
+    mcfunction,
+    scoreboard::{self, Scoreboard, Types}
+};
+
+#[test]
+fn overflow_test() {
+    let x = Scoreboard {
+        name : "x".to_string(),
+        data_type : Types::Int,
+        scope : vec!["TEST".to_string()]
+    };
+    assert_eq!(calc_num(&x, "+", 1, Overflow::Wrapping).unwrap(), vec!["scoreboard players add #TEST.x MCPP.var 1"]);
+    let checked = calc_num(&x, "+", 1, Overflow::Checked).unwrap().join("\n");
+    println!("{}", checked);
+    assert!(checked.contains("execute unless score #Calc.OVERFLOW.flag MCPP.var matches 0 run scoreboard players set #Calc.EXIT MCPP.var 1"));
+    assert!(checked.contains("execute unless score #Calc.OVERFLOW.flag MCPP.var matches 0 run return fail"));
+    let saturated = calc_score(&x, "*", &x, Overflow::Saturating).unwrap().join("\n");
+    println!("{}", saturated);
+    assert!(saturated.contains("execute if score #Calc.OVERFLOW.flag MCPP.var matches 1 run scoreboard players set #TEST.x MCPP.var 2147483647"));
+    assert_eq!(calc_score(&x, "%", &x, Overflow::Checked).unwrap().len(), 1);
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+/// What happens when an arithmetic operation overflows a score.
+pub enum Overflow {
+    /// Wraps around silently, as scoreboards of Minecraft do.
+    #[default]
+    Wrapping,
+    /// Raises a runtime error, then stops the function, leaving every block it is in.
+    Checked,
+    /// Clamps the result at the bounds of a score.
+    Saturating
+}
+impl Overflow {
+    pub fn from_name(name:&str) -> Option<Overflow> {
+        match name.trim() {
+            "wrapping" => Some(Overflow::Wrapping),
+            "checked" => Some(Overflow::Checked),
+            "saturating" => Some(Overflow::Saturating),
+            _ => None
+        }
+    }
+}
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            Overflow::Wrapping => "wrapping",
+            Overflow::Checked => "checked",
+            Overflow::Saturating => "saturating"
+        })
+    }
+}
+
+fn work_score(name:&str) -> Scoreboard {
+    Scoreboard {
+        name : name.to_string(),
+        data_type : Types::Int,
+        scope : vec!["Calc".to_string(), "OVERFLOW".to_string()]
+    }
+}
+/// The pure function to get commands to calc a score and a number, guarded by given mode.
+pub fn calc_num(score:&Scoreboard, operator:&str, num:i32, overflow:Overflow) -> Result<Vec<String>, EvaluateError> {
+    match operator {
+        "+" | "-" => guard(score, operator, &num.to_string(), overflow, vec![score.pure_calc_num(operator, num)?], |operand| {
+            vec![operand.pure_assign_value(num)]
+        }),
+        _ => calc_score(score, operator, &Scoreboard::from(num), overflow)
+    }
+}
+/// The pure function to get commands to calc two scores, guarded by given mode.
+pub fn calc_score(score:&Scoreboard, operator:&str, source:&Scoreboard, overflow:Overflow) -> Result<Vec<String>, EvaluateError> {
+    guard(score, operator, &source.to_string(), overflow, vec![score.pure_calc_score(operator, source)], |operand| {
+        vec![operand.pure_assign_score(source)]
+    })
+}
+/// Wrap a primitive operation with commands to detect an overflow of it.
+///
+/// Both operands are copied before the operation, because the source can be the same as score.
+/// Then #Calc.OVERFLOW.flag is set to 1 if the result overflowed upward, or -1 if it overflowed downward.
+fn guard(
+    score:&Scoreboard,
+    operator:&str,
+    source_name:&str,
+    overflow:Overflow,
+    body:Vec<String>,
+    copy_source:impl Fn(&Scoreboard) -> Vec<String>
+) -> Result<Vec<String>, EvaluateError> {
+    // A modulo never overflows.
+    if overflow == Overflow::Wrapping || operator == "%" {
+        return Ok(body);
+    }
+    let (before, operand, flag, check) = (work_score("before"), work_score("operand"), work_score("flag"), work_score("check"));
+    let set_flag = |conditions:&[String], value:i32| format!(
+        "execute {} run {}",
+        conditions.iter().map(|f| format!("if {}", f)).collect::<Vec<String>>().join(" "),
+        flag.pure_assign_value(value)
+    );
+    let (positive, negative) = (operand.pure_match_range("1.."), operand.pure_match_range("..-1"));
+    let min = i32::MIN.to_string();
+
+    let mut res = vec![before.pure_assign_score(score)];
+    res.append(&mut copy_source(&operand));
+    res.extend(body);
+    res.push(flag.pure_assign_value(0));
+    match operator {
+        "+" => {
+            res.push(set_flag(&[positive, score.pure_compare_score("<", &before)], 1));
+            res.push(set_flag(&[negative, score.pure_compare_score(">", &before)], -1));
+        },
+        "-" => {
+            res.push(set_flag(&[positive, score.pure_compare_score(">", &before)], -1));
+            res.push(set_flag(&[negative, score.pure_compare_score("<", &before)], 1));
+        },
+        // The product is right only if dividing it by one operand gives the other back.
+        // -1 * -2147483648 passes the check, because the division overflows as well, so it is flagged explicitly.
+        "*" => {
+            res.push(check.pure_assign_score(score));
+            res.push(format!("execute unless {} run {}", before.pure_match_range("0"), check.pure_calc_score("/", &before)));
+            res.push(format!(
+                "execute unless {} unless {} run {}",
+                before.pure_match_range("0"),
+                check.pure_compare_score("=", &operand),
+                flag.pure_assign_value(1)
+            ));
+            res.push(set_flag(&[before.pure_match_range("-1"), operand.pure_match_range(&min)], 1));
+            res.push(set_flag(&[flag.pure_match_range("1"), before.pure_match_range("..-1"), positive], -1));
+            res.push(set_flag(&[flag.pure_match_range("1"), before.pure_match_range("1.."), negative], -1));
+        },
+        "/" => res.push(set_flag(&[before.pure_match_range(&min), operand.pure_match_range("-1")], 1)),
+        _ => return Err(EvaluateError::UnknownOperatorGiven(operator.to_string()))
+    }
+    match overflow {
+        Overflow::Checked => {
+            let fits = flag.pure_match_range("0");
+            res.push(format!(
+                "execute unless {} run {}",
+                fits,
+                mcfunction::pure_runtime_error(&format!("{} {}= {} overflowed.", score, operator, source_name))
+            ));
+            // Blocks calling this one see the exit flag, then leave as well.
+            res.push(format!("execute unless {} run {}", fits, scoreboard::get_exit_flag().pure_assign_value(1)));
+            res.push(format!("execute unless {} run return fail", fits));
+        },
+        Overflow::Saturating => {
+            res.push(format!("execute if {} run {}", flag.pure_match_range("1"), score.pure_assign_value(i32::MAX)));
+            res.push(format!("execute if {} run {}", flag.pure_match_range("-1"), score.pure_assign_value(i32::MIN)));
+        },
+        Overflow::Wrapping => ()
+    }
+    Ok(res)
+}
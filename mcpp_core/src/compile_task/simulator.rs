@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+
+use super::{mcfunction, MCFunction};
+
+/// Where a command leaves the function which runs it.
+enum Flow {
+    /// The function goes on, with the result of the command, or None if it failed.
+    Next(Option<i32>),
+    /// The function returns a value, or None by `return fail`.
+    Return(Option<i32>)
+}
+
+/// A small interpreter of commands generated by the compiler, so tests can check what a pack does, not only how it looks.
+///
+/// Only commands the compiler emits for scores, storages and functions are run. Commands like tellraw are skipped,
+/// and any other command panics, so a test never passes by ignoring something.
+pub struct Simulator {
+    functions : HashMap<String, String>,
+    scores : HashMap<String, i32>,
+    /// Lists of compounds on storages, like the call stack, by the storage and the name of the list.
+    lists : HashMap<String, Vec<HashMap<String, i32>>>,
+    depth : usize
+}
+
+/// The pure function to split a path of a storage like frames[-1].n onto the list, the index and the rest.
+fn split_path(path:&str) -> (&str, i64, Option<&str>) {
+    let (list, rest) = path.split_once('[').expect("Only paths in a list are simulated.");
+    let (index, rest) = rest.split_once(']').unwrap();
+    (list, index.parse().unwrap(), rest.strip_prefix('.'))
+}
+/// The pure function to check a value is in a range like 1, ..-1 or 0..5.
+fn in_range(value:i32, range:&str) -> bool {
+    match range.split_once("..") {
+        Some((min, max)) => min.parse().map_or(true, |f:i32| f <= value) && max.parse().map_or(true, |f:i32| value <= f),
+        None => range.parse() == Ok(value)
+    }
+}
+
+impl Simulator {
+    /// Load given function and its children, then run functions in minecraft:load like a world does.
+    pub fn new(root:&MCFunction) -> Simulator {
+        let mut simulator = Simulator {
+            functions : HashMap::new(),
+            scores : HashMap::new(),
+            lists : HashMap::new(),
+            depth : 0
+        };
+        let mut loaded:Vec<String> = Vec::new();
+        let mut stack = vec![root];
+        while let Some(function) = stack.pop() {
+            let id = mcfunction::get_id(&function.namespace, &function.scope, &function.name);
+            if function.tags.iter().any(|f| f == "minecraft:load") {
+                loaded.push(id.clone());
+            }
+            simulator.functions.insert(id, function.inside.clone());
+            stack.extend(&function.child_func);
+        }
+        for id in loaded {
+            simulator.call(&id);
+        }
+        simulator
+    }
+    /// Get a score of a fake player on MCPP.var, or None if it is not set.
+    pub fn score(&self, player:&str) -> Option<i32> {
+        self.scores.get(&format!("{} MCPP.var", player)).copied()
+    }
+    /// Run a function, then return the value it returned.
+    pub fn call(&mut self, id:&str) -> Option<i32> {
+        let inside = self.functions.get(id).unwrap_or_else(|| panic!("{} is not found.", id)).clone();
+        self.depth += 1;
+        assert!(self.depth < 512, "Functions are nested too deeply.");
+        let mut returned = None;
+        for line in inside.lines().map(|f| f.trim()).filter(|f| !f.is_empty() && !f.starts_with('#')) {
+            if let Flow::Return(value) = self.run(line) {
+                returned = value;
+                break;
+            }
+        }
+        self.depth -= 1;
+        returned
+    }
+    fn get(&self, player:&str, objective:&str) -> i32 {
+        self.scores.get(&format!("{} {}", player, objective)).copied().unwrap_or(0)
+    }
+    fn set(&mut self, player:&str, objective:&str, value:i32) -> Flow {
+        self.scores.insert(format!("{} {}", player, objective), value);
+        Flow::Next(Some(value))
+    }
+    fn operate(&mut self, player:&str, objective:&str, operator:&str, source:i32) -> Flow {
+        let value = self.get(player, objective);
+        let result = match operator {
+            "=" => source,
+            "+=" => value.wrapping_add(source),
+            "-=" => value.wrapping_sub(source),
+            "*=" => value.wrapping_mul(source),
+            "/=" if source == 0 => return Flow::Next(None),
+            "/=" => value.wrapping_sub(value.wrapping_rem(source).wrapping_add(source).wrapping_rem(source)).wrapping_div(source),
+            "%=" if source == 0 => return Flow::Next(None),
+            "%=" => value.wrapping_rem(source).wrapping_add(source).wrapping_rem(source),
+            "<" => value.min(source),
+            ">" => value.max(source),
+            _ => panic!("{} is not an operator of scores.", operator)
+        };
+        self.set(player, objective, result)
+    }
+    fn list(&mut self, storage:&str, list:&str) -> &mut Vec<HashMap<String, i32>> {
+        self.lists.entry(format!("{} {}", storage, list)).or_default()
+    }
+    /// Get the compound at a path of a list, or None if the list doesn't have it.
+    fn element(&mut self, storage:&str, path:&str) -> Option<(&mut HashMap<String, i32>, Option<String>)> {
+        let (list, index, rest) = split_path(path);
+        let rest = rest.map(|f| f.to_string());
+        let elements = self.list(storage, list);
+        let index = if index < 0 { elements.len() as i64 + index } else { index };
+        elements.get_mut(usize::try_from(index).ok()?).map(|f| (f, rest))
+    }
+    fn run(&mut self, command:&str) -> Flow {
+        let tokens:Vec<&str> = command.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["scoreboard", "objectives", ..] => Flow::Next(Some(0)),
+            ["scoreboard", "players", "set", player, objective, value] => self.set(player, objective, value.parse().unwrap()),
+            ["scoreboard", "players", "add", player, objective, value] => self.operate(player, objective, "+=", value.parse().unwrap()),
+            ["scoreboard", "players", "remove", player, objective, value] => self.operate(player, objective, "-=", value.parse().unwrap()),
+            ["scoreboard", "players", "reset", player, objective] => {
+                self.scores.remove(&format!("{} {}", player, objective));
+                Flow::Next(Some(0))
+            },
+            ["scoreboard", "players", "get", player, objective] => Flow::Next(self.scores.get(&format!("{} {}", player, objective)).copied()),
+            ["scoreboard", "players", "operation", player, objective, operator, source, source_objective] => {
+                let source_value = self.get(source, source_objective);
+                if *operator == "><" {
+                    let value = self.get(player, objective);
+                    self.set(source, source_objective, value);
+                    return self.set(player, objective, source_value)
+                }
+                self.operate(player, objective, operator, source_value)
+            },
+            ["function", id] => match self.call(id) {
+                Some(value) => Flow::Next(Some(value)),
+                None => Flow::Next(Some(0))
+            },
+            ["return", "fail"] => Flow::Return(None),
+            ["return", "run", ..] => match self.run(&tokens[2..].join(" ")) {
+                Flow::Next(value) | Flow::Return(value) => Flow::Return(value)
+            },
+            ["return", value] => Flow::Return(Some(value.parse().unwrap())),
+            ["data", "modify", "storage", storage, list, "append", "value", "{}"] => {
+                self.list(storage, list).push(HashMap::new());
+                Flow::Next(Some(1))
+            },
+            ["data", "remove", "storage", storage, path] => {
+                let (list, index, _) = split_path(path);
+                let elements = self.list(storage, list);
+                let index = if index < 0 { elements.len() as i64 + index } else { index };
+                elements.remove(index as usize);
+                Flow::Next(Some(1))
+            },
+            ["data", "get", "storage", storage, path, ..] => Flow::Next(
+                self.element(storage, path).and_then(|(compound, key)| compound.get(&key.unwrap()).copied())
+            ),
+            ["execute", ..] => self.execute(&tokens[1..]),
+            ["tellraw" | "say" | "tag" | "advancement" | "schedule" | "effect", ..] => Flow::Next(Some(0)),
+            _ => panic!("The simulator doesn't know {}", command)
+        }
+    }
+    fn execute(&mut self, tokens:&[&str]) -> Flow {
+        let mut stores:Vec<(&str, &str, &str, &str)> = Vec::new();
+        let mut rest = tokens;
+        loop {
+            match rest {
+                [condition @ ("if" | "unless"), "score", player, objective, "matches", range, others @ ..] => {
+                    let matched = self.scores.get(&format!("{} {}", player, objective)).is_some_and(|f| in_range(*f, range));
+                    if matched != (*condition == "if") {
+                        return Flow::Next(None)
+                    }
+                    rest = others;
+                },
+                [condition @ ("if" | "unless"), "score", player, objective, operator, source, source_objective, others @ ..] => {
+                    let (value, source) = (self.get(player, objective), self.get(source, source_objective));
+                    let compared = match *operator {
+                        "<" => value < source,
+                        "<=" => value <= source,
+                        "=" => value == source,
+                        ">=" => value >= source,
+                        ">" => value > source,
+                        _ => panic!("{} is not a comparison of scores.", operator)
+                    };
+                    if compared != (*condition == "if") {
+                        return Flow::Next(None)
+                    }
+                    rest = others;
+                },
+                ["store", kind @ ("result" | "success"), "score", player, objective, others @ ..] => {
+                    stores.push((kind, "score", player, objective));
+                    rest = others;
+                },
+                ["store", "result", "storage", storage, path, _, _, others @ ..] => {
+                    stores.push(("result", "storage", storage, path));
+                    rest = others;
+                },
+                ["run", command @ ..] => {
+                    let flow = self.run(&command.join(" "));
+                    if let Flow::Next(result) = flow {
+                        for (kind, target, holder, place) in stores {
+                            let value = if kind == "success" { result.is_some() as i32 } else { result.unwrap_or(0) };
+                            match target {
+                                "score" => { self.set(holder, place, value); },
+                                _ => {
+                                    let (compound, key) = self.element(holder, place).expect("The frame is not pushed.");
+                                    compound.insert(key.unwrap(), value);
+                                }
+                            }
+                        }
+                    }
+                    return flow
+                },
+                _ => panic!("The simulator doesn't know execute {}", rest.join(" "))
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! The standard library.
+//!
+//! abs, min, max, clamp, sign and sqrt can be called from any formula unless a function of the same name is declared.
+//! They are generated once in the mcpp_std folder of the pack, only if they are called.
+//!
+//! sin, cos, atan2 and lerp take floats, and angles are in degrees.
+//! sin, cos and atan2 are looked up from tables built for the float precision of the project.
+//!
+//! random(a, b) returns an int in \[a, b\], and random_seed(seed) makes the following numbers a fixed sequence.
+
+use std::collections::{BTreeMap, HashMap};
+
+use regex::Regex;
+
+use super::{
+    evaluater::{self, EvaluateError},
+    scoreboard::{float, Calcable, Types},
+    mcfunction, CompileTask, McVersion, MCFunction, Scoreboard
+};
+
+mod random;
+mod trig;
+
+/// The folder where functions of the standard library are generated, shared by the whole pack.
+pub const STD_FOLDER:&str = "mcpp_std";
+
+#[test]
+fn std_lib_test() {
+    let mut compiler = CompileTask::new();
+    let compiled = compiler.compile("a:int = abs(0 - 3) + max(1, 2); f:float = sqrt(2.0); s:int = sign(f)", "test").unwrap();
+    println!("{}", compiled.inside);
+    assert!(compiled.inside.contains("function test:mcpp_std/int/abs"));
+    assert!(compiled.inside.contains("function test:mcpp_std/float/sqrt"));
+    assert!(compiled.inside.contains("function test:mcpp_std/float/sign"));
+    // Only the int variant of abs is called.
+    assert_eq!(compiled.child_func.iter().filter(|f| f.name == "abs").count(), 1);
+    let sqrt_iter = compiled.child_func.iter().find(|f| f.callment == "function test:mcpp_std/float/sqrt_iter").unwrap();
+    println!("{}", sqrt_iter.inside);
+    assert!(sqrt_iter.inside.contains("run function test:mcpp_std/float/sqrt_iter"));
+    // Functions which aren't called are never generated, nor helpers of them.
+    assert!(!compiled.child_func.iter().any(|f| ["min", "sin", "sin_table", "random_next"].contains(&f.name.as_str())));
+    assert_eq!(evaluater::guess_type(&compiler, "clamp(1.5, 0, 1)").unwrap(), Types::Flt(3));
+    assert_eq!(evaluater::guess_type(&compiler, "sign(1.5)").unwrap(), Types::Int);
+    assert_eq!(evaluater::guess_type(&compiler, "sin(30)").unwrap(), Types::Flt(3));
+
+    let mut compiler = CompileTask::new();
+    let shadowed = compiler.compile("fn abs(x:int) -> int { return x }; a:int = abs(1.5)", "test").unwrap();
+    assert!(shadowed.inside.contains("function test:abs"));
+
+    let mut compiler = CompileTask::new();
+    let rolled = compiler.compile("random_seed(7); r:int = random(1, 6)", "test").unwrap();
+    println!("{}", rolled.inside);
+    assert!(rolled.inside.contains("function test:mcpp_std/int/random_seed"));
+    assert!(rolled.inside.contains("execute store result storage mcpp:macro random.b int 1 run scoreboard players get #mcpp_std.int.random.b MCPP.var"));
+    assert!(rolled.inside.contains("function test:mcpp_std/int/random with storage mcpp:macro random"));
+}
+
+/// The pure function to get the key of the float variant of a function of the standard library.
+fn float_variant(name:&str) -> String {
+    format!("{}<float>", name)
+}
+
+/// Declare every functions of the standard library for given namespace.
+///
+/// Each function has a variant for int and one for float of the default precision,
+/// and the int variant is keyed by the name of the function, so it is found by [`CompileTask::get_function`].
+pub fn declare(namespace:&str, float_precision:u32, mc_version:McVersion) -> Result<HashMap<String, MCFunction>, EvaluateError> {
+    let mut functions:HashMap<String, MCFunction> = HashMap::new();
+    for (variant, data_type) in [("int", Types::Int), ("float", Types::Flt(float_precision))] {
+        let scope = vec![STD_FOLDER.to_string(), variant.to_string()];
+        let std_functions:[(&str, &[&str], Types); 6] = [
+            ("abs", &["x"], data_type.clone()),
+            ("min", &["a", "b"], data_type.clone()),
+            ("max", &["a", "b"], data_type.clone()),
+            ("clamp", &["x", "lo", "hi"], data_type.clone()),
+            ("sign", &["x"], Types::Int),
+            ("sqrt", &["x"], data_type.clone())
+        ];
+        for (name, params, ret_type) in std_functions {
+            let mut function = std_function(namespace, &scope, name, params, &data_type, ret_type);
+            (function.inside, function.child_func) = generate_inside(&function, namespace)?;
+            let key = if variant == "int" { name.to_string() } else { float_variant(name) };
+            functions.insert(key, function);
+        }
+    }
+    // Functions below have only one variant, so they are keyed by their names.
+    functions.extend(trig::declare(namespace, float_precision)?);
+    functions.extend(random::declare(namespace, mc_version)?);
+    Ok(functions)
+}
+/// Make an empty function of the standard library.
+///
+/// Parameters and the returned value are scoped under the name of the function, like #mcpp_std.int.abs.x.
+fn std_function(namespace:&str, scope:&[String], name:&str, params:&[&str], data_type:&Types, ret_type:Types) -> MCFunction {
+    let mut function = MCFunction::generate(name, String::new(), namespace, scope);
+    let mut args_scope = scope.to_vec();
+    args_scope.push(name.to_string());
+    function.args = params
+        .iter()
+        .map(|f| Scoreboard {
+            name : f.to_string(),
+            data_type : data_type.clone(),
+            scope : args_scope.clone()
+        })
+        .collect();
+    function.ret_container = Scoreboard {
+        name : "RETURN_VALUE".to_string(),
+        data_type : ret_type,
+        scope : args_scope
+    };
+    function
+}
+/// Get the variant of given function which fits the arguments.
+///
+/// A function of the standard library takes floats if any of arguments is a float,
+/// and other functions are returned as they are.
+pub fn specialize<'a>(compiler:&'a CompileTask, function:&'a MCFunction, args:&str) -> &'a MCFunction {
+    let is_std = function.scope.first().is_some_and(|f| f == STD_FOLDER);
+    let takes_float = evaluater::split_arguments(args)
+        .iter()
+        .any(|f| matches!(evaluater::guess_type(compiler, f), Ok(Types::Flt(_))));
+    if is_std && takes_float {
+        compiler.std_functions.get(&float_variant(&function.name)).unwrap_or(function)
+    } else {
+        function
+    }
+}
+/// Get functions of the standard library called from given function and its children, and helpers called from them.
+///
+/// Every function is taken out of the tree it is declared in, so a helper shared by some of them,
+/// like the table of sin, is generated once whichever of them is called.
+pub fn collect_used(root:&MCFunction, std_functions:&HashMap<String, MCFunction>) -> Vec<MCFunction> {
+    let mut declared:HashMap<String, MCFunction> = HashMap::new();
+    for f in std_functions.values() {
+        flatten(f.clone(), &mut declared);
+    }
+    let call_regex = Regex::new(r"function ([\w.\-]+:[\w./\-]+)").unwrap();
+    let mut pending:Vec<String> = Vec::new();
+    collect_calls(root, &call_regex, &mut pending);
+    let mut used:BTreeMap<String, MCFunction> = BTreeMap::new();
+    while let Some(id) = pending.pop() {
+        if let Some(f) = declared.remove(&id) {
+            collect_calls(&f, &call_regex, &mut pending);
+            used.insert(id, f);
+        }
+    }
+    used.into_values().collect()
+}
+/// Put given function and its children into the map by their ids, without children.
+fn flatten(mut function:MCFunction, into:&mut HashMap<String, MCFunction>) {
+    for f in std::mem::take(&mut function.child_func) {
+        flatten(f, into);
+    }
+    into.insert(mcfunction::get_id(&function.namespace, &function.scope, &function.name), function);
+}
+/// Collect ids of functions called in commands of given function and its children.
+fn collect_calls(function:&MCFunction, call_regex:&Regex, ids:&mut Vec<String>) {
+    for line in function.inside.lines().filter(|f| !f.trim_start().starts_with('#')) {
+        ids.extend(call_regex.captures_iter(line).map(|f| f[1].to_string()));
+    }
+    for f in &function.child_func {
+        collect_calls(f, call_regex, ids);
+    }
+}
+/// The pure function to get commands of a function of the standard library, and helper functions of it.
+///
+/// Floats of the same precision are compared as they are, so every functions except sqrt are the same as int.
+fn generate_inside(function:&MCFunction, namespace:&str) -> Result<(String, Vec<MCFunction>), EvaluateError> {
+    let ret = &function.ret_container;
+    let args = &function.args;
+    let mut helpers:Vec<MCFunction> = Vec::new();
+    let inside = match function.name.as_str() {
+        "abs" => vec![
+            ret.pure_assign_score(&args[0]),
+            format!("execute if {} run {}", args[0].pure_match_range("..-1"), ret.pure_assign_value(0)),
+            format!("execute if {} run {}", args[0].pure_match_range("..-1"), ret.pure_calc_score("-", &args[0]))
+        ],
+        "min" => vec![ret.pure_assign_score(&args[0]), ret.pure_min_score(&args[1])],
+        "max" => vec![ret.pure_assign_score(&args[0]), ret.pure_max_score(&args[1])],
+        "clamp" => vec![
+            ret.pure_assign_score(&args[0]),
+            ret.pure_max_score(&args[1]),
+            ret.pure_min_score(&args[2])
+        ],
+        "sign" => vec![
+            ret.pure_assign_value(0),
+            format!("execute if {} run {}", args[0].pure_match_range("1.."), ret.pure_assign_value(1)),
+            format!("execute if {} run {}", args[0].pure_match_range("..-1"), ret.pure_assign_value(-1))
+        ],
+        "sqrt" => {
+            let (iteration, mut inside) = generate_sqrt(function, namespace)?;
+            helpers.push(iteration);
+            inside.insert(0, ret.pure_assign_value(0));
+            inside
+        },
+        _ => Vec::new()
+    };
+    Ok((inside.join("\n"), helpers))
+}
+/// The pure function to get commands of sqrt, and the function of Newton's iteration.
+///
+/// It finds floor(sqrt(N)) by y = (y + N / y) / 2 from y >= sqrt(N), until y stops decreasing.
+/// For a float, N is x * M, and N / y is the division of floats, so the result is scaled by M as well.
+///
+/// (y + N / y) / 2 is calculated as q + (y - q) / 2 where q = N / y, because y + q can overflow.
+fn generate_sqrt(function:&MCFunction, namespace:&str) -> Result<(MCFunction, Vec<String>), EvaluateError> {
+    let x = &function.args[0];
+    let mut work_scope = x.scope.clone();
+    work_scope.pop();
+    work_scope.push(format!("{}_iter", function.name));
+    let work = |name:&str| Scoreboard {
+        name : name.to_string(),
+        data_type : x.data_type.clone(),
+        scope : work_scope.clone()
+    };
+    let (y, q, z, next) = (work("y"), work("q"), work("z"), work("continue"));
+    let mut iteration = MCFunction::generate(&format!("{}_iter", function.name), String::new(), namespace, &function.scope);
+
+    let mut inside = vec![q.pure_assign_score(x)];
+    match x.data_type {
+        Types::Flt(_) => inside.push(q.calc("/", &Calcable::Scr(&y))?),
+        _ => inside.push(q.pure_calc_score("/", &y))
+    }
+    inside.append(&mut vec![
+        z.pure_assign_score(&y),
+        z.pure_calc_score("-", &q),
+        z.pure_calc_num("/", 2)?,
+        z.pure_calc_score("+", &q),
+        next.pure_assign_value(0),
+        format!("execute if {} run {}", z.pure_compare_score("<", &y), next.pure_assign_value(1)),
+        format!("execute if {} run {}", next.pure_match_range("1"), y.pure_assign_score(&z)),
+        format!("execute if {} run {}", next.pure_match_range("1"), iteration.callment)
+    ]);
+    iteration.inside = inside.join("\n");
+
+    let mut entry = vec![y.pure_assign_score(x)];
+    // sqrt(x * M) is smaller than M if x < 1, so the first guess must be M at least.
+    if let Types::Flt(p) = x.data_type {
+        let magnif = Scoreboard::from(float::get_magnif(p));
+        entry.push(y.pure_max_score(&magnif));
+    }
+    entry.push(format!("execute if {} run {}", x.pure_match_range("1.."), iteration.callment));
+    entry.push(format!("execute if {} run {}", x.pure_match_range("1.."), function.ret_container.pure_assign_score(&y)));
+    Ok((iteration, entry))
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use crate::compile_task::{
+    evaluater::EvaluateError,
+    mcfunction,
+    scoreboard::Types,
+    McVersion, MCFunction, Scoreboard
+};
+use super::{std_function, STD_FOLDER};
+
+#[test]
+fn random_test() {
+    let new = declare("test", McVersion::RANDOM).unwrap();
+    println!("{}", new["random"].inside);
+    assert!(new["random"].is_macro);
+    assert_eq!(new["random"].callment, "function test:mcpp_std/int/random with storage mcpp:macro random");
+    assert!(new["random"].inside.contains("run random value $(a)..$(b)"));
+
+    let old = declare("test", McVersion::parse("1.20.1").unwrap()).unwrap();
+    println!("{}", old["random"].inside);
+    assert!(!old["random"].is_macro);
+    assert!(old["random"].inside.contains("summon marker"));
+    assert!(old["random_seed"].inside.contains("scoreboard players set #mcpp_std.random.seeded MCPP.var 1"));
+}
+
+/// Multiplier and increment of the linear congruential generator, the same as rand() of C.
+const LCG:(i32, i32) = (1103515245, 12345);
+
+/// Get a scoreboard which keeps the state of random numbers, like #mcpp_std.random.state.
+fn state_score(name:&str) -> Scoreboard {
+    Scoreboard {
+        name : name.to_string(),
+        data_type : Types::Int,
+        scope : vec![STD_FOLDER.to_string(), "random".to_string()]
+    }
+}
+
+/// Declare random(a, b), which returns an int in \[a, b\], and random_seed(seed).
+///
+/// On Minecraft 1.20.3 or later, random is a function macro which runs `random value a..b`.
+/// On older versions, a linear congruential generator is seeded by the UUID of a marker summoned each time.
+///
+/// Once random_seed is called, random follows the generator from the seed on every version,
+/// so the sequence is the same each time, which is useful for tests.
+pub fn declare(namespace:&str, mc_version:McVersion) -> Result<HashMap<String, MCFunction>, EvaluateError> {
+    let scope = vec![STD_FOLDER.to_string(), "int".to_string()];
+    let (state, seeded) = (state_score("state"), state_score("seeded"));
+    let is_seeded = seeded.pure_match_range("1");
+    let mut functions:HashMap<String, MCFunction> = HashMap::new();
+
+    let mut random = std_function(namespace, &scope, "random", &["a", "b"], &Types::Int, Types::Int);
+    let mut next = MCFunction::generate("random_next", String::new(), namespace, &scope);
+    next.inside = generate_next(&random)?.join("\n");
+
+    let mut inside:Vec<String> = Vec::new();
+    if mc_version >= McVersion::RANDOM {
+        random.is_macro = true;
+        random.callment = mcfunction::get_macro_callment(namespace, &scope, &random.name);
+        inside.push(format!(
+            "$execute unless {} run {}",
+            is_seeded,
+            random.ret_container.pure_store_result("random value $(a)..$(b)")
+        ));
+    } else {
+        let tag = "MCPP.random";
+        inside.append(&mut vec![
+            format!("execute unless {} run summon marker ~ ~ ~ {{Tags:[\"{}\"]}}", is_seeded, tag),
+            format!(
+                "execute unless {} run {}",
+                is_seeded,
+                state.pure_store_result(&format!("data get entity @e[type=marker,tag={},limit=1] UUID[0]", tag))
+            ),
+            format!("execute unless {} run kill @e[type=marker,tag={}]", is_seeded, tag),
+            format!("execute unless {} run {}", is_seeded, next.callment)
+        ]);
+    }
+    inside.push(format!("execute if {} run {}", is_seeded, next.callment));
+    random.inside = inside.join("\n");
+    random.child_func.push(next);
+    functions.insert(random.name.clone(), random);
+
+    let mut random_seed = std_function(namespace, &scope, "random_seed", &["seed"], &Types::Int, Types::Non);
+    random_seed.inside = [
+        state.pure_assign_score(&random_seed.args[0]),
+        seeded.pure_assign_value(1)
+    ].join("\n");
+    functions.insert(random_seed.name.clone(), random_seed);
+    Ok(functions)
+}
+/// The pure function to get commands to step the generator, then take a number in \[a, b\] from the state.
+///
+/// Higher bits of the state are used, because lower bits of the generator repeat in short periods.
+/// The span is positive if a <= b, so the floor modulo of Minecraft never gives a negative number.
+fn generate_next(random:&MCFunction) -> Result<Vec<String>, EvaluateError> {
+    let (a, b, ret) = (&random.args[0], &random.args[1], &random.ret_container);
+    let state = state_score("state");
+    let span = state_score("span");
+    let multiplier = Scoreboard::from(LCG.0);
+    Ok(vec![
+        state.pure_calc_score("*", &multiplier),
+        state.pure_calc_num("+", LCG.1)?,
+        ret.pure_assign_score(&state),
+        ret.pure_calc_num("/", 65536)?,
+        span.pure_assign_score(b),
+        span.pure_calc_score("-", a),
+        span.pure_calc_num("+", 1)?,
+        ret.pure_calc_score("%", &span),
+        ret.pure_calc_score("+", a)
+    ])
+}
@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use crate::compile_task::{
+    dispatch,
+    evaluater::EvaluateError,
+    scoreboard::{float, Calcable, Types},
+    MCFunction, Scoreboard
+};
+use super::{std_function, STD_FOLDER};
+
+#[test]
+fn trig_test() {
+    let functions = declare("test", 3).unwrap();
+    let sin = &functions["sin"];
+    println!("{}", sin.inside);
+    assert_eq!(sin.callment, "function test:mcpp_std/float/sin");
+    let table = &sin.child_func[0];
+    assert_eq!(table.callment, "function test:mcpp_std/float/sin_table");
+    let entries = table.child_func.iter().map(|f| f.inside.clone()).collect::<Vec<String>>().join("\n");
+    assert!(entries.contains("matches 30 run scoreboard players set #mcpp_std.float.sin_table.value MCPP.var 500"));
+    assert!(entries.contains("matches 90 run scoreboard players set #mcpp_std.float.sin_table.value MCPP.var 1000"));
+    assert!(functions["cos"].inside.contains("scoreboard players add #mcpp_std.float.sin_table.index MCPP.var 90"));
+    assert_eq!(functions["atan2"].args.len(), 2);
+    assert_eq!(functions["lerp"].ret_container.data_type, Types::Flt(3));
+
+    // Tables are generated only by uses of functions which look them up.
+    let names = |raw:&str| {
+        let compiled = crate::compile_task::CompileTask::new().compile(raw, "test").unwrap();
+        compiled.child_func.iter().map(|f| f.name.clone()).collect::<Vec<String>>()
+    };
+    let cos = names("a:float = cos(60.0)");
+    assert!(cos.contains(&"sin_table".to_string()) && !cos.contains(&"sin".to_string()));
+    assert!(!cos.contains(&"atan_table".to_string()));
+    let lerp = names("a:float = lerp(1.0, 2.0, 0.5)");
+    assert!(!lerp.iter().any(|f| f.ends_with("_table")));
+    assert!(names("a:float = atan2(1.0, 1.0)").contains(&"atan_table".to_string()));
+}
+
+/// The number of entries of the table of sin, one for each degree.
+const DEGREES:i32 = 360;
+
+/// Declare trigonometric functions of the standard library, and lerp.
+///
+/// Angles are in degrees, as rotations of Minecraft are.
+/// Values are looked up from tables built for given precision, and interpolated linearly between two entries.
+pub fn declare(namespace:&str, float_precision:u32) -> Result<HashMap<String, MCFunction>, EvaluateError> {
+    let data_type = Types::Flt(float_precision);
+    let scope = vec![STD_FOLDER.to_string(), "float".to_string()];
+    let mut functions:HashMap<String, MCFunction> = HashMap::new();
+
+    let sin_table = generate_table(namespace, &scope, "sin_table", float_precision, 0..=DEGREES, |i| {
+        (i as f64).to_radians().sin()
+    });
+    for (name, offset) in [("sin", 0), ("cos", 90)] {
+        let mut function = std_function(namespace, &scope, name, &["x"], &data_type, data_type.clone());
+        function.inside = generate_periodic(&function, &sin_table, offset)?.join("\n");
+        // The table is declared as a helper of sin, and cos calls it too.
+        // Only called functions are generated, so it is generated if either of them is called.
+        if offset == 0 {
+            function.child_func.push(sin_table.clone());
+        }
+        functions.insert(name.to_string(), function);
+    }
+
+    let steps = float::get_magnif(float_precision.min(2));
+    let mut atan2 = std_function(namespace, &scope, "atan2", &["y", "x"], &data_type, data_type.clone());
+    let atan_table = generate_table(namespace, &scope, "atan_table", float_precision, 0..=steps + 1, |i| {
+        (i as f64 / steps as f64).atan().to_degrees()
+    });
+    atan2.inside = generate_atan2(&atan2, &atan_table, steps)?.join("\n");
+    atan2.child_func.push(atan_table);
+    functions.insert("atan2".to_string(), atan2);
+
+    let mut lerp = std_function(namespace, &scope, "lerp", &["a", "b", "t"], &data_type, data_type.clone());
+    let (a, b, t, ret) = (&lerp.args[0], &lerp.args[1], &lerp.args[2], &lerp.ret_container);
+    lerp.inside = [
+        ret.pure_assign_score(b),
+        ret.pure_calc_score("-", a),
+        ret.calc("*", &Calcable::Scr(t))?,
+        ret.pure_calc_score("+", a)
+    ].join("\n");
+    functions.insert("lerp".to_string(), lerp);
+    Ok(functions)
+}
+/// The index and the value of a table, named like #mcpp_std.float.sin_table.index.
+fn table_scores(table:&MCFunction, float_precision:u32) -> (Scoreboard, Scoreboard) {
+    let mut scope = table.scope.clone();
+    scope.push(table.name.clone());
+    let score = |name:&str, data_type:Types| Scoreboard {
+        name : name.to_string(),
+        data_type,
+        scope : scope.clone()
+    };
+    (score("index", Types::Int), score("value", Types::Flt(float_precision)))
+}
+/// Make a function to set the value of a table to f(index) scaled by M, by a dispatch on the index.
+///
+/// Each value is calculated in compile time, so the table has no error other than rounding.
+fn generate_table(
+    namespace:&str,
+    scope:&[String],
+    name:&str,
+    float_precision:u32,
+    indexes:impl Iterator<Item = i32>,
+    f:impl Fn(i32) -> f64
+) -> MCFunction {
+    let mut table = MCFunction::generate(name, String::new(), namespace, scope);
+    let (index, value) = table_scores(&table, float_precision);
+    let magnif = float::get_magnif(float_precision) as f64;
+    let branches = indexes
+        .map(|i| (i, value.pure_assign_value((f(i) * magnif).round() as i32)))
+        .collect::<Vec<(i32, String)>>();
+    let (inside, tree) = dispatch::dispatch(&index, &branches, name, namespace, scope);
+    table.inside = inside.join("\n");
+    table.child_func = tree;
+    table
+}
+/// The pure function to get commands to look up the value between the index and the next of it.
+///
+/// The index must be set before, and fraction is a float in \[0, 1) which tells the place between them.
+fn interpolate(table:&MCFunction, into:&Scoreboard, fraction:&Scoreboard) -> Result<Vec<String>, EvaluateError> {
+    let (index, value) = table_scores(table, float::precision_of(into));
+    Ok(vec![
+        table.callment.clone(),
+        into.pure_assign_score(&value),
+        index.pure_calc_num("+", 1)?,
+        table.callment.clone(),
+        value.pure_calc_score("-", into),
+        value.calc("*", &Calcable::Scr(fraction))?,
+        into.pure_calc_score("+", &value)
+    ])
+}
+/// The pure function to get commands of sin, or cos when offset is 90.
+///
+/// The integer part of x in degrees is the index of the table, taken modulo 360,
+/// and the fractional part is interpolated. Both are floored, so they are right for negative angles as well.
+fn generate_periodic(function:&MCFunction, table:&MCFunction, offset:i32) -> Result<Vec<String>, EvaluateError> {
+    let (x, ret) = (&function.args[0], &function.ret_container);
+    let precision = float::precision_of(x);
+    let magnif = float::get_magnif(precision);
+    let (index, _) = table_scores(table, precision);
+    let fraction = work_score(function, "fraction", Types::Flt(precision));
+
+    let mut inside = vec![
+        index.pure_assign_score(x),
+        index.pure_calc_num("/", magnif)?,
+        fraction.pure_assign_score(x),
+        fraction.pure_calc_num("%", magnif)?
+    ];
+    if offset != 0 {
+        inside.push(index.pure_calc_num("+", offset)?);
+    }
+    inside.push(index.pure_calc_num("%", DEGREES)?);
+    inside.append(&mut interpolate(table, ret, &fraction)?);
+    Ok(inside)
+}
+/// The pure function to get commands of atan2.
+///
+/// The table has atan of ratios in \[0, 1\], so the ratio is taken as the smaller of |x| and |y| over the larger,
+/// then the angle is reflected into the right octant. The result is in (-180, 180\], and atan2(0, 0) is 0.
+fn generate_atan2(function:&MCFunction, table:&MCFunction, steps:i32) -> Result<Vec<String>, EvaluateError> {
+    let (y, x, ret) = (&function.args[0], &function.args[1], &function.ret_container);
+    let precision = float::precision_of(x);
+    let magnif = float::get_magnif(precision);
+    let (index, _) = table_scores(table, precision);
+    let work = |name:&str| work_score(function, name, Types::Flt(precision));
+    let (ax, ay, swapped, fraction, reflected) = (work("ax"), work("ay"), work("swapped"), work("fraction"), work("reflected"));
+    let (negative_x, negative_y) = (x.pure_match_range("..-1"), y.pure_match_range("..-1"));
+    let is_swapped = swapped.pure_match_range("1");
+    let one = Scoreboard::from(1);
+
+    let mut inside:Vec<String> = Vec::new();
+    for (absolute, of, negative) in [(&ax, x, &negative_x), (&ay, y, &negative_y)] {
+        inside.push(absolute.pure_assign_score(of));
+        inside.push(format!("execute if {} run {}", negative, absolute.pure_assign_value(0)));
+        inside.push(format!("execute if {} run {}", negative, absolute.pure_calc_score("-", of)));
+    }
+    // ret is the ratio at first, then the angle.
+    inside.append(&mut vec![
+        swapped.pure_assign_value(0),
+        format!("execute if {} run {}", ay.pure_compare_score(">", &ax), swapped.pure_assign_value(1)),
+        ret.pure_assign_score(&ay),
+        format!("execute if {} run {}", is_swapped, ret.pure_assign_score(&ax)),
+        format!("execute if {} run {}", is_swapped, ax.pure_assign_score(&ay)),
+        // The denominator is 0 only when both are 0, then the ratio is 0 as well.
+        ax.pure_max_score(&one),
+        ret.calc("/", &Calcable::Scr(&ax))?,
+        index.pure_assign_score(ret),
+        index.pure_calc_num("/", magnif / steps)?,
+        fraction.pure_assign_score(ret),
+        fraction.pure_calc_num("%", magnif / steps)?,
+        fraction.pure_calc_num("*", steps)?
+    ]);
+    inside.append(&mut interpolate(table, ret, &fraction)?);
+    for (condition, base) in [(&is_swapped, 90_i32), (&negative_x, 180)] {
+        inside.push(reflected.pure_assign_value(base.saturating_mul(magnif)));
+        inside.push(reflected.pure_calc_score("-", ret));
+        inside.push(format!("execute if {} run {}", condition, ret.pure_assign_score(&reflected)));
+    }
+    inside.push(reflected.pure_assign_value(0));
+    inside.push(reflected.pure_calc_score("-", ret));
+    inside.push(format!("execute if {} run {}", negative_y, ret.pure_assign_score(&reflected)));
+    Ok(inside)
+}
+/// Get a scoreboard used while a function is running, named like #mcpp_std.float.sin.fraction.
+fn work_score(function:&MCFunction, name:&str, data_type:Types) -> Scoreboard {
+    Scoreboard {
+        name : name.to_string(),
+        data_type,
+        scope : function.ret_container.scope.clone()
+    }
+}
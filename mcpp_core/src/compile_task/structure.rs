@@ -0,0 +1,51 @@
+use super::{evaluater, CompileTask, SentenceError};
+use super::scoreboard::Types;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A user-defined structure, declared like
+/// ```ignore
+/// struct Vec3 { x: float, y: float, z: float }
+/// ```
+/// A variable of a structure isn't a scoreboard by itself.
+///
+/// It expands onto one scoreboard per field, and they are scoped under the name of the variable.
+///
+/// For example, `pos.x` of `pos:Vec3` will be #pos.x in Minecraft.
+pub struct Structure {
+    pub name   : String,
+    pub fields : Vec<(String, Types)>
+}
+
+impl Structure {
+    /// Parse the inside of a struct sentence.
+    ///
+    /// Fields can be separated by both of commas and semicolons.
+    pub fn parse(compiler:&CompileTask, name:&str, inside:&[String]) -> Result<Structure, SentenceError> {
+        let mut fields:Vec<(String, Types)> = Vec::new();
+        for field in inside
+            .iter()
+            .flat_map(|f| f.split(','))
+            .map(|f| f.trim())
+            .filter(|f| !f.is_empty())
+        {
+            let (field_name, annotation) = match field.split_once(':') {
+                Some(s) => (s.0.trim(), s.1.trim()),
+                None => return Err(SentenceError::InvalidStructureField(field.to_string()))
+            };
+            if field_name.is_empty() || fields.iter().any(|f| f.0 == field_name) {
+                return Err(SentenceError::InvalidStructureField(field.to_string()))
+            }
+            let data_type = match evaluater::to_type(compiler, annotation) {
+                Ok(o) => o,
+                Err(e) => return Err(SentenceError::InvalidFormula(e))
+            };
+            fields.push((field_name.to_string(), data_type));
+        }
+        Ok(
+            Structure {
+                name : name.to_string(),
+                fields : fields
+            }
+        )
+    }
+}
@@ -0,0 +1,110 @@
+use super::{call_graph, evaluater, schedule, CompileTask, Line, McVersion, Sentence, SentenceError, Signature};
+use super::scoreboard::Types;
+
+#[test]
+fn type_check_test() {
+    let mut compiler = CompileTask::new();
+    let checked = compiler.compile(
+        "fn greet() { a:int = 1 }
+        fn twice(n:float) -> float { return n * 2 }
+        x:int = greet();
+        flag:bool = 1 + 0.5;
+        y = twice(1) + 0.5",
+        "test"
+    );
+    match checked {
+        Err(SentenceError::TypeErrors(errors)) => {
+            println!("{}", errors.join("\n"));
+            assert_eq!(errors.len(), 2);
+            assert!(errors[0].starts_with("x:int = greet()"));
+            assert!(errors[1].starts_with("flag:bool = 1 + 0.5"));
+        },
+        _ => panic!("Type errors must be reported.")
+    }
+    let mut compiler = CompileTask::new();
+    assert_eq!(evaluater::guess_type(&compiler, "1 + 0.5").unwrap(), Types::Flt(3));
+    compiler.compile("y = 1 + 0.5", "test").unwrap();
+    assert_eq!(compiler.get_variable(&"y".to_string()).unwrap().data_type, Types::Flt(3));
+}
+
+impl CompileTask {
+    /// Check types of every formula in given sentence before any command is emitted.
+    ///
+    /// Variables and items are declared on a copy of the compiler in the same order as compiling,
+    /// and errors are collected through the whole sentence, so all of them are reported at once.
+    pub(super) fn check_types(&self, root:&Sentence) -> Result<(), SentenceError> {
+        let mut errors:Vec<String> = Vec::new();
+        schedule::check_every(root)?;
+        self.spawn_slave().check_sentence(root, &mut errors)?;
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(SentenceError::TypeErrors(errors))
+        }
+    }
+    fn check_sentence(&mut self, sentence:&Sentence, errors:&mut Vec<String>) -> Result<(), SentenceError> {
+        self.sentence_path.push(sentence.name.clone());
+        if sentence.is_function() {
+            let signature = Signature::parse(&sentence.specifiers.join(" "))?;
+            let (args, ret_container) = self.solve_signature(&signature)?;
+            self.current_function = Some(call_graph::key(&self.sentence_path));
+            self.frame.clear();
+            for arg in &args {
+                self.declare_variable(&arg.name, arg.clone());
+            }
+            self.ret_container = Some(ret_container);
+        }
+        self.in_macro = sentence.is_macro();
+        self.apply_overflow(sentence)?;
+        sentence.get_after()?;
+        // The namespace doesn't matter, because only types of declared items are used.
+        self.declare_items(sentence, "")?;
+        if sentence.specifiers.first().is_some_and(|f| f == "if") {
+            let condition = sentence.specifiers[1..].join(" ");
+            if let Err(e) = evaluater::check_condition(self, &condition) {
+                errors.push(format!("{} -> {}", sentence.specifiers.join(" "), e));
+            }
+        }
+
+        // Minecraft before 1.20.3 can't leave a function, so lines after a return would run as well.
+        let mut returned = false;
+        for line in &sentence.parsed_lines {
+            if returned && self.mc_version < McVersion::RETURN_FAIL && !matches!(line, Line::Comment(_)) {
+                errors.push(format!("{} -> {}", sentence.specifiers.join(" "), SentenceError::StatementAfterReturn(self.mc_version)));
+                break;
+            }
+            returned = match line {
+                Line::Formula(f) => f.trim().starts_with("return "),
+                Line::Sentence(s) => s.can_exit(self.overflow),
+                Line::Comment(_) => returned
+            };
+            match line {
+                Line::Formula(f) => match schedule::parse_wait(f) {
+                    Some(duration) => { sentence.check_wait(f, duration)?; },
+                    None => if let Err(e) = evaluater::check(self, f) {
+                        errors.push(format!("{} -> {}", f, e));
+                    }
+                },
+                Line::Sentence(s) if s.is_structure() || s.is_enumeration() => (),
+                Line::Sentence(s) if s.is_match() => {
+                    let formula = s.specifiers[1..].join(" ");
+                    match evaluater::guess_type(self, &formula) {
+                        Ok(Types::Int | Types::Enum(_)) => (),
+                        Ok(t) => errors.push(format!("{} -> {}", s.specifiers.join(" "), SentenceError::UnmatchableType(t))),
+                        Err(e) => errors.push(format!("{} -> {}", s.specifiers.join(" "), e))
+                    }
+                    for arm in s.parsed_lines.iter().filter_map(|f| match f { Line::Sentence(s) => Some(s), _ => None }) {
+                        if let Err(e) = self.spawn_slave().check_sentence(arm, errors) {
+                            errors.push(format!("{} -> {}", arm.specifiers.join(" "), e));
+                        }
+                    }
+                },
+                Line::Sentence(s) => if let Err(e) = self.spawn_slave().check_sentence(s, errors) {
+                    errors.push(format!("{} -> {}", s.specifiers.join(" "), e));
+                },
+                Line::Comment(_) => ()
+            }
+        }
+        Ok(())
+    }
+}
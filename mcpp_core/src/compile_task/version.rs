@@ -0,0 +1,111 @@
+use std::fmt;
+
+#[test]
+fn version_test() {
+    let old = McVersion::parse("1.20.1").unwrap();
+    let new = McVersion::parse("1.21").unwrap();
+    assert!(old < McVersion::MACRO);
+    assert!(new >= McVersion::MACRO);
+    assert_eq!(new.to_string(), "1.21.0");
+    assert!(McVersion::parse("1.x").is_none());
+
+    assert_eq!(old.pack_format(), 15);
+    assert_eq!(McVersion::parse("1.20.4").unwrap().pack_format(), 26);
+    assert_eq!(McVersion::LATEST.pack_format(), 61);
+    assert_eq!(old.folder("function"), "functions");
+    assert_eq!(new.folder("function"), "function");
+}
+
+/// The pack_format of data packs, by the first version which uses it.
+const PACK_FORMATS:[(McVersion, u32); 19] = [
+    (McVersion::new(1, 13, 0), 4),
+    (McVersion::new(1, 15, 0), 5),
+    (McVersion::new(1, 16, 2), 6),
+    (McVersion::new(1, 17, 0), 7),
+    (McVersion::new(1, 18, 0), 8),
+    (McVersion::new(1, 18, 2), 9),
+    (McVersion::new(1, 19, 0), 10),
+    (McVersion::new(1, 19, 4), 12),
+    (McVersion::new(1, 20, 0), 15),
+    (McVersion::new(1, 20, 2), 18),
+    (McVersion::new(1, 20, 3), 26),
+    (McVersion::new(1, 20, 5), 41),
+    (McVersion::new(1, 21, 0), 48),
+    (McVersion::new(1, 21, 2), 57),
+    (McVersion::new(1, 21, 4), 61),
+    (McVersion::new(1, 21, 5), 71),
+    (McVersion::new(1, 21, 6), 80),
+    (McVersion::new(1, 21, 7), 81),
+    (McVersion::new(1, 21, 8), 81)
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// A version of Minecraft, like 1.20.1.
+///
+/// Some of features depend on it, because the generated commands must be supported by the version.
+pub struct McVersion {
+    pub major : u32,
+    pub minor : u32,
+    pub patch : u32
+}
+
+impl McVersion {
+    /// The version used when no version is specified.
+    pub const LATEST:McVersion = McVersion { major : 1, minor : 21, patch : 4 };
+    /// The first version which supports data packs, so older ones can't be targeted.
+    pub const DATAPACK:McVersion = McVersion { major : 1, minor : 13, patch : 0 };
+    /// The first version which reads overlays and supported_formats of pack.mcmeta.
+    pub const OVERLAY:McVersion = McVersion { major : 1, minor : 20, patch : 2 };
+    /// The first version which supports function macros.
+    pub const MACRO:McVersion = McVersion { major : 1, minor : 20, patch : 2 };
+    /// The first version which supports `return fail`, used to stop a function by a runtime error.
+    pub const RETURN_FAIL:McVersion = McVersion { major : 1, minor : 20, patch : 3 };
+    /// The version from which `random value a..b` is used by random(a, b), instead of a generator on scoreboards.
+    pub const RANDOM:McVersion = McVersion { major : 1, minor : 20, patch : 3 };
+
+    /// The first version whose folders of data packs are singular, like function instead of functions.
+    pub const SINGULAR_FOLDERS:McVersion = McVersion { major : 1, minor : 21, patch : 0 };
+
+    /// Make a version from its numbers, used to write the table of versions shortly.
+    pub const fn new(major:u32, minor:u32, patch:u32) -> McVersion {
+        McVersion { major, minor, patch }
+    }
+    /// The pack_format of data packs for this version.
+    /// 
+    /// Versions newer than the table use the latest format of it.
+    pub fn pack_format(&self) -> u32 {
+        PACK_FORMATS
+            .iter()
+            .rev()
+            .find(|f| f.0 <= *self)
+            .unwrap_or(&PACK_FORMATS[0])
+            .1
+    }
+    /// The name of the folder of given kind of files in data packs, like function or functions.
+    pub fn folder(&self, singular:&str) -> String {
+        if *self >= McVersion::SINGULAR_FOLDERS { singular.to_string() } else { format!("{}s", singular) }
+    }
+    /// Parse a version like 1.20.1. The patch version can be omitted.
+    pub fn parse(raw:&str) -> Option<McVersion> {
+        let numbers = raw
+            .trim()
+            .split('.')
+            .map(|f| f.parse::<u32>().ok())
+            .collect::<Option<Vec<u32>>>()?;
+        match numbers.as_slice() {
+            [major, minor] => Some(McVersion { major : *major, minor : *minor, patch : 0 }),
+            [major, minor, patch] => Some(McVersion { major : *major, minor : *minor, patch : *patch }),
+            _ => None
+        }
+    }
+}
+impl Default for McVersion {
+    fn default() -> Self {
+        McVersion::LATEST
+    }
+}
+impl fmt::Display for McVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
@@ -1,9 +1,10 @@
 pub mod compile_task;
 
 // MC++ Crates
-use compile_task::{CompileTask, MCFunction, SentenceError};
+use compile_task::{scoreboard::float, CompileTask, MCFunction, McVersion, Overflow, SentenceError};
 
 // Outer Crates
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::prelude::*;
 
@@ -24,8 +25,24 @@ fn load_a_file_inside(path:&str) -> String {
 /// Compile given text file and return result as MCFunction.
 /// 
 /// This is a wrapper of [`compile_task::CompileTask::compile()`]
-pub fn compile_a_file(path:&str) -> Result<MCFunction, SentenceError> {
+/// 
+/// mc_version is a version of Minecraft like 1.20.1, which the datapack targets,
+/// and float_precision is digits under the decimal point of `float`.
+/// 
+/// overflow is one of checked, saturating and wrapping, which is used unless a sentence has `#[overflow(...)]`.
+/// 
+/// features are enabled for `#[cfg(feature = "...")]`.
+pub fn compile_a_file(path:&str, mc_version:&str, float_precision:u32, overflow:&str, features:HashSet<String>) -> Result<MCFunction, SentenceError> {
     let mut compiler = CompileTask::new();
+    compiler.float_precision = Some(float_precision)
+        .filter(|f| *f <= float::MAX_PRECISION)
+        .ok_or(SentenceError::InvalidFloatPrecision(float_precision))?;
+    compiler.features = features;
+    compiler.overflow = Overflow::from_name(overflow)
+        .ok_or(SentenceError::UnknownOverflowMode(overflow.to_string()))?;
+    compiler.mc_version = McVersion::parse(mc_version)
+        .filter(|f| *f >= McVersion::DATAPACK)
+        .ok_or(SentenceError::UnknownVersion(mc_version.to_string()))?;
     compiler.compile(
         &load_a_file_inside(path),
         {
@@ -40,5 +57,12 @@ pub fn compile_a_file(path:&str) -> Result<MCFunction, SentenceError> {
 }
 #[test]
 fn compile_test() {
-    println!("{}", compile_a_file(&"C:/Projects/MCPP-Rewrite/test_code.mcpp".to_string()).unwrap());
-}
\ No newline at end of file
+    println!("{}", compile_a_file(&"C:/Projects/MCPP-Rewrite/test_code.mcpp".to_string(), "1.21.4", 3, "wrapping", HashSet::new()).unwrap());
+}
+#[test]
+fn precision_test() {
+    assert!(matches!(
+        compile_a_file("missing.mcpp", "1.21.4", 10, "wrapping", HashSet::new()),
+        Err(SentenceError::InvalidFloatPrecision(10))
+    ));
+}